@@ -0,0 +1,96 @@
+//! Text written as CDATA sections.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// The name `CData` tells our serializers apart from other newtype structs by.
+pub(crate) const CDATA_TOKEN: &str = "$serde_xml_rs::CData";
+
+/// A value written as a CDATA section, like `<script><![CDATA[a < b]]></script>`,
+/// rather than as escaped text.
+///
+/// Text is read the same way whether it is escaped or in CDATA sections, so
+/// deserializing a `CData<T>` is no different from deserializing a `T`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::{from_str, to_string, CData};
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Query {
+///     sql: CData<String>,
+/// }
+/// # fn main() {
+/// let query = Query { sql: CData("SELECT * FROM t WHERE a < 1".to_string()) };
+/// let xml = to_string(&query).unwrap();
+/// assert_eq!(xml, "<Query><sql><![CDATA[SELECT * FROM t WHERE a < 1]]></sql></Query>");
+/// assert_eq!(from_str::<Query>(&xml).unwrap(), query);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CData<T>(pub T);
+
+impl<T> Deref for CData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for CData<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Serialize> Serialize for CData<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(CDATA_TOKEN, &self.0)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for CData<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        T::deserialize(deserializer).map(CData)
+    }
+}
+
+/// Displays text as CDATA sections. A section can't contain `]]>`, so that
+/// is split across two: `]]` ends one and `>` starts the next.
+pub(crate) struct CDataSections<'a>(pub &'a str);
+
+impl<'a> fmt::Display for CDataSections<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("<![CDATA[")?;
+        let mut parts = self.0.split("]]>");
+        if let Some(first) = parts.next() {
+            formatter.write_str(first)?;
+        }
+        for part in parts {
+            formatter.write_str("]]]]><![CDATA[>")?;
+            formatter.write_str(part)?;
+        }
+        formatter.write_str("]]>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        assert_eq!(CDataSections("").to_string(), "<![CDATA[]]>");
+        assert_eq!(CDataSections("a < b & c").to_string(), "<![CDATA[a < b & c]]>");
+        assert_eq!(
+            CDataSections("x[1]]>y]]>").to_string(),
+            "<![CDATA[x[1]]]]><![CDATA[>y]]]]><![CDATA[>]]>"
+        );
+    }
+}
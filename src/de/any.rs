@@ -0,0 +1,185 @@
+use std::io::Read;
+
+use serde::de::{self, IntoDeserializer};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::reader::XmlEvent;
+
+use de::Deserializer;
+use error::{Error, ErrorKind, Result};
+use super::source::Source;
+
+/// A self-describing snapshot of an element, built when the caller gives no
+/// type hint (`deserialize_any`).
+///
+/// An element without attributes or content is a unit, one with text only is
+/// that text, and anything else is a map of its attributes followed by its
+/// children, where same-named children are grouped into a sequence. Text is
+/// always a string, so that types buffering it (flattened structs, untagged
+/// enums) read `42` into a `String` field the same as `forty-two`.
+pub(crate) enum Value {
+    Unit,
+    Text(String),
+    Seq(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+/// An element `read_any` has started but not finished.
+struct Partial {
+    name: OwnedName,
+    // The key the element goes under in its parent
+    key: String,
+    entries: Vec<(String, Value)>,
+    children: Vec<(String, Vec<Value>)>,
+}
+
+impl Partial {
+    fn add_child(&mut self, key: String, value: Value) {
+        match self.children.iter().position(|(name, _)| *name == key) {
+            Some(index) => self.children[index].1.push(value),
+            None => self.children.push((key, vec![value])),
+        }
+    }
+
+    fn finish(self) -> Value {
+        let Partial { mut entries, mut children, .. } = self;
+        if entries.is_empty() {
+            match children.len() {
+                0 => return Value::Unit,
+                1 if children[0].0 == "$value" && children[0].1.len() == 1 => {
+                    return children.remove(0).1.remove(0);
+                },
+                _ => {},
+            }
+        }
+        entries.extend(children.into_iter().map(|(key, mut values)| {
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Value::Seq(values)
+            };
+            (key, value)
+        }));
+        Value::Map(entries)
+    }
+}
+
+impl<'de, R: Read, S: Source<'de>> Deserializer<R, S> {
+    /// Reads the next text node or the whole next element into a `Value`.
+    ///
+    /// Open elements are kept on a stack of their own rather than the call
+    /// stack, so deeply nested input fails with the depth limit, if one is
+    /// set, and not with a stack overflow.
+    pub(crate) fn read_any(&mut self) -> Result<Value> {
+        let mut open = match self.next()? {
+            XmlEvent::Characters(s) => return Ok(Value::Text(s)),
+            XmlEvent::StartElement { name, attributes, .. } => {
+                vec![self.start_any(name, attributes, String::new())]
+            },
+            other => {
+                return Err(ErrorKind::UnexpectedToken(
+                    "StartElement | Characters".to_string(),
+                    format!("{:?}", other),
+                ).into())
+            },
+        };
+        loop {
+            let key = match *self.peek()? {
+                XmlEvent::StartElement { ref name, .. } => Some(name.local_name.clone()),
+                XmlEvent::Characters(_) => None,
+                _ => {
+                    let element = open.pop().expect("an open element");
+                    self.expect_end_element(element.name.clone())?;
+                    let key = element.key.clone();
+                    let value = element.finish();
+                    match open.last_mut() {
+                        Some(parent) => parent.add_child(key, value),
+                        None => return Ok(value),
+                    }
+                    continue;
+                },
+            };
+            match (key, self.next()?) {
+                (Some(key), XmlEvent::StartElement { name, attributes, .. }) => {
+                    let element = self.start_any(name, attributes, key);
+                    open.push(element);
+                },
+                (None, XmlEvent::Characters(s)) => {
+                    let parent = open.last_mut().expect("an open element");
+                    parent.add_child("$value".to_string(), Value::Text(s));
+                },
+                _ => unreachable!("the event was peeked"),
+            }
+        }
+    }
+
+    fn start_any(&self, name: OwnedName, attributes: Vec<OwnedAttribute>, key: String) -> Partial {
+        let entries = attributes
+            .into_iter()
+            .map(|attr| {
+                let key = self.config.prefixed_attribute(&attr.name.local_name);
+                (key, Value::Text(attr.value))
+            })
+            .collect();
+        Partial {
+            name,
+            key,
+            entries,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Unit => visitor.visit_unit(),
+            Value::Text(text) => visitor.visit_string(text),
+            Value::Seq(values) => {
+                let mut seq = SeqDeserializer::new(values.into_iter());
+                let result = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(result)
+            },
+            Value::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let result = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(result)
+            },
+        }
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Unit => visitor.visit_str(""),
+            Value::Text(text) => visitor.visit_string(text),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
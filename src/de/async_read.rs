@@ -0,0 +1,463 @@
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+
+use de::DeserializerConfig;
+use error::{ErrorKind, Result};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+mod sealed {
+    use std::io;
+    use std::task::{Context, Poll};
+
+    /// The part of the various `AsyncRead` traits the deserializers need.
+    pub trait PollRead {
+        fn poll_read_into(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+    }
+}
+
+use self::sealed::PollRead;
+
+/// A `tokio::io::AsyncRead` to deserialize from.
+#[cfg(feature = "tokio")]
+pub struct Tokio<R>(R);
+
+#[cfg(feature = "tokio")]
+impl<R: ::tokio::io::AsyncRead + Unpin> PollRead for Tokio<R> {
+    fn poll_read_into(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buf = ::tokio::io::ReadBuf::new(buf);
+        match Pin::new(&mut self.0).poll_read(cx, &mut buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(buf.filled().len())),
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A `futures::io::AsyncRead` to deserialize from.
+#[cfg(feature = "futures")]
+pub struct Futures<R>(R);
+
+#[cfg(feature = "futures")]
+impl<R: ::futures_io::AsyncRead + Unpin> PollRead for Futures<R> {
+    fn poll_read_into(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+/// Deserializes a whole document from a `tokio::io::AsyncRead`.
+///
+/// The document is read into memory without blocking and then deserialized
+/// like `from_reader` does. Use `stream_from_tokio_reader` for documents too
+/// large for that.
+#[cfg(feature = "tokio")]
+pub fn from_tokio_reader<R, T>(reader: R) -> ReadDocument<Tokio<R>, T>
+where
+    R: ::tokio::io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    ReadDocument::new(Tokio(reader))
+}
+
+/// Deserializes the children of the root element read from a
+/// `tokio::io::AsyncRead` one at a time, skipping those not called `name` if
+/// it is given. Only one child is held in memory at a time.
+#[cfg(feature = "tokio")]
+pub fn stream_from_tokio_reader<R, T>(reader: R, name: Option<&str>) -> ReadItems<Tokio<R>, T>
+where
+    R: ::tokio::io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    ReadItems::new(Tokio(reader), name)
+}
+
+/// Deserializes a whole document from a `futures::io::AsyncRead`.
+///
+/// The document is read into memory without blocking and then deserialized
+/// like `from_reader` does. Use `stream_from_futures_reader` for documents
+/// too large for that.
+#[cfg(feature = "futures")]
+pub fn from_futures_reader<R, T>(reader: R) -> ReadDocument<Futures<R>, T>
+where
+    R: ::futures_io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    ReadDocument::new(Futures(reader))
+}
+
+/// Deserializes the children of the root element read from a
+/// `futures::io::AsyncRead` one at a time, skipping those not called `name`
+/// if it is given. Only one child is held in memory at a time.
+#[cfg(feature = "futures")]
+pub fn stream_from_futures_reader<R, T>(
+    reader: R,
+    name: Option<&str>,
+) -> ReadItems<Futures<R>, T>
+where
+    R: ::futures_io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    ReadItems::new(Futures(reader), name)
+}
+
+/// A reader and the bytes read from it but not yet consumed.
+struct Buffer<R> {
+    reader: R,
+    bytes: Vec<u8>,
+    // All bytes read so far, for the document size limit
+    read: usize,
+    max_size: Option<usize>,
+}
+
+impl<R: PollRead> Buffer<R> {
+    fn new(reader: R) -> Self {
+        Buffer {
+            reader,
+            bytes: Vec::new(),
+            read: 0,
+            max_size: None,
+        }
+    }
+
+    /// Reads another chunk, resolving to `false` at the end of the input.
+    fn poll_fill(&mut self, cx: &mut Context) -> Poll<Result<bool>> {
+        let len = self.bytes.len();
+        self.bytes.resize(len + CHUNK_SIZE, 0);
+        let result = self.reader.poll_read_into(cx, &mut self.bytes[len..]);
+        let read = match result {
+            Poll::Ready(Ok(read)) => read,
+            _ => 0,
+        };
+        self.bytes.truncate(len + read);
+        self.read += read;
+        match self.max_size {
+            Some(max) if self.read > max => {
+                Poll::Ready(Err(ErrorKind::DocumentSizeLimitExceeded(max).into()))
+            },
+            _ => result.map(|result| Ok(result? > 0)),
+        }
+    }
+}
+
+/// A future deserializing a whole document, created by `from_tokio_reader`
+/// or `from_futures_reader`.
+pub struct ReadDocument<R, T> {
+    buffer: Buffer<R>,
+    config: DeserializerConfig,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<R: PollRead, T: DeserializeOwned> ReadDocument<R, T> {
+    fn new(reader: R) -> Self {
+        ReadDocument {
+            buffer: Buffer::new(reader),
+            config: DeserializerConfig::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Deserializes with `config` rather than the default settings. Its
+    /// document size limit also applies to reading the document into memory.
+    pub fn with_config(mut self, config: DeserializerConfig) -> Self {
+        self.buffer.max_size = config.limits.document_size;
+        self.config = config;
+        self
+    }
+}
+
+impl<R: PollRead + Unpin, T: DeserializeOwned> Future for ReadDocument<R, T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
+        let this = self.get_mut();
+        loop {
+            match this.buffer.poll_fill(cx) {
+                Poll::Ready(Ok(true)) => {},
+                Poll::Ready(Ok(false)) => {
+                    let mut de = this.config.clone().create_deserializer(&this.buffer.bytes[..]);
+                    return Poll::Ready(T::deserialize(&mut de));
+                },
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A stream deserializing the children of the root element, created by
+/// `stream_from_tokio_reader` or `stream_from_futures_reader`.
+///
+/// The input is only split into children here; each of them is then parsed
+/// on its own, after a copy of the XML declaration and DOCTYPE and inside a
+/// copy of the root start tag, so that the encoding, entities and namespace
+/// declarations still apply. The input must use an encoding that keeps
+/// markup in ASCII, which rules out UTF-16.
+pub struct ReadItems<R, T> {
+    buffer: Buffer<R>,
+    config: DeserializerConfig,
+    name: Option<String>,
+    // The markup before the root element
+    prolog: Vec<u8>,
+    // The root start tag and name, once read
+    root: Option<(Vec<u8>, String)>,
+    depth: usize,
+    // Elements seen so far, for the element limit
+    elements: usize,
+    // How much of the buffer has been split into markup and text so far
+    scanned: usize,
+    item_start: Option<usize>,
+    finished: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+enum Scanned {
+    Item(Range<usize>),
+    NeedMore,
+    End,
+}
+
+impl<R: PollRead, T: DeserializeOwned> ReadItems<R, T> {
+    fn new(reader: R, name: Option<&str>) -> Self {
+        ReadItems {
+            buffer: Buffer::new(reader),
+            config: DeserializerConfig::new(),
+            name: name.map(str::to_string),
+            prolog: Vec::new(),
+            root: None,
+            depth: 0,
+            elements: 0,
+            scanned: 0,
+            item_start: None,
+            finished: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Deserializes each child with `config` rather than the default
+    /// settings. Its depth, element and document size limits also apply to
+    /// splitting the input into children, across the whole document.
+    pub fn with_config(mut self, config: DeserializerConfig) -> Self {
+        self.buffer.max_size = config.limits.document_size;
+        self.config = config;
+        self
+    }
+
+    /// Scans the buffer up to the end of the next child of the root element.
+    fn scan(&mut self) -> Result<Scanned> {
+        loop {
+            let bytes = &self.buffer.bytes;
+            let start = match bytes[self.scanned..].iter().position(|&b| b == b'<') {
+                Some(offset) => self.scanned + offset,
+                None => {
+                    self.scanned = bytes.len();
+                    return Ok(Scanned::NeedMore);
+                },
+            };
+            self.scanned = start;
+            let end = match markup_len(&bytes[start..]) {
+                Some(len) => start + len,
+                None => return Ok(Scanned::NeedMore),
+            };
+            self.scanned = end;
+
+            let markup = &bytes[start..end];
+            if markup.starts_with(b"</") {
+                self.depth = self.depth.saturating_sub(1);
+                match self.depth {
+                    0 => return Ok(Scanned::End),
+                    1 => if let Some(item_start) = self.item_start.take() {
+                        return Ok(Scanned::Item(item_start..end));
+                    },
+                    _ => {},
+                }
+            } else if markup.starts_with(b"<!") || markup.starts_with(b"<?") {
+                if self.root.is_none() {
+                    self.prolog.extend_from_slice(markup);
+                }
+            } else {
+                let limits = &self.config.limits;
+                self.elements += 1;
+                match limits.elements {
+                    Some(max) if self.elements > max => {
+                        return Err(ErrorKind::ElementLimitExceeded(max).into());
+                    },
+                    _ => {},
+                }
+                limits.check_depth(self.depth + 1)?;
+                let empty = markup.ends_with(b"/>");
+                match self.depth {
+                    0 => {
+                        let name = String::from_utf8_lossy(tag_name(markup)).into_owned();
+                        self.root = Some((markup.to_vec(), name));
+                        if empty {
+                            return Ok(Scanned::End);
+                        }
+                    },
+                    1 if empty => return Ok(Scanned::Item(start..end)),
+                    1 => self.item_start = Some(start),
+                    _ => {},
+                }
+                if !empty {
+                    self.depth += 1;
+                }
+            }
+        }
+    }
+
+    /// Drops everything before the current child (or the markup not yet
+    /// complete) from the buffer.
+    fn compact(&mut self) {
+        let keep = self.item_start.unwrap_or(self.scanned);
+        self.buffer.bytes.drain(..keep);
+        self.scanned -= keep;
+        self.item_start = None;
+    }
+
+    fn deserialize_item(&self, item: Range<usize>) -> Option<Result<T>> {
+        let markup = &self.buffer.bytes[item];
+        if let Some(ref name) = self.name {
+            let local_name = match tag_name(markup).iter().rposition(|&b| b == b':') {
+                Some(colon) => &tag_name(markup)[colon + 1..],
+                None => tag_name(markup),
+            };
+            if local_name != name.as_bytes() {
+                return None;
+            }
+        }
+        let (ref root, ref root_name) = *self.root.as_ref()?;
+        let mut document = self.prolog.clone();
+        document.extend_from_slice(root);
+        document.extend_from_slice(markup);
+        document.extend_from_slice(format!("</{}>", root_name).as_bytes());
+        // The whole document has been held to the element and size limits
+        let mut config = self.config.clone();
+        config.limits.elements = None;
+        config.limits.document_size = None;
+        config.create_deserializer(&document[..]).into_stream().next()
+    }
+
+    /// Polls for the next child. Errors splitting the input end the stream,
+    /// but a child that fails to deserialize doesn't, being parsed on its own.
+    fn poll_item(&mut self, cx: &mut Context) -> Poll<Option<Result<T>>> {
+        loop {
+            let scanned = match self.scan() {
+                Ok(scanned) => scanned,
+                Err(error) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(error)));
+                },
+            };
+            match scanned {
+                Scanned::Item(item) => {
+                    let result = self.deserialize_item(item);
+                    self.compact();
+                    if result.is_some() {
+                        return Poll::Ready(result);
+                    }
+                },
+                Scanned::End => {
+                    self.finished = true;
+                    return Poll::Ready(None);
+                },
+                Scanned::NeedMore => {
+                    if self.item_start.is_none() {
+                        self.compact();
+                    }
+                    match self.buffer.poll_fill(cx) {
+                        Poll::Ready(Ok(true)) => {},
+                        Poll::Ready(Ok(false)) => {
+                            self.finished = true;
+                            let error = io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "the document ended before its root element",
+                            );
+                            return Poll::Ready(Some(Err(error.into())));
+                        },
+                        Poll::Ready(Err(error)) => {
+                            self.finished = true;
+                            return Poll::Ready(Some(Err(error)));
+                        },
+                        Poll::Pending => return Poll::Pending,
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<R: PollRead + Unpin, T: DeserializeOwned> Stream for ReadItems<R, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<T>>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(None);
+        }
+        this.poll_item(cx)
+    }
+}
+
+/// Returns the length of the markup `bytes` starts with, if it is complete.
+fn markup_len(bytes: &[u8]) -> Option<usize> {
+    let find = |pattern: &[u8]| {
+        bytes
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map(|offset| offset + pattern.len())
+    };
+    if bytes.starts_with(b"<!--") {
+        find(b"-->")
+    } else if bytes.starts_with(b"<![CDATA[") {
+        find(b"]]>")
+    } else if bytes.starts_with(b"<?") {
+        find(b"?>")
+    } else {
+        // Tags and declarations: quoted strings and an internal DTD subset
+        // may contain `>`
+        let mut quote = None;
+        let mut brackets = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            match (quote, b) {
+                (Some(q), _) if q == b => quote = None,
+                (Some(_), _) => {},
+                (None, b'"') | (None, b'\'') => quote = Some(b),
+                (None, b'[') => brackets += 1,
+                (None, b']') => brackets -= 1,
+                (None, b'>') if brackets == 0 => return Some(i + 1),
+                _ => {},
+            }
+        }
+        None
+    }
+}
+
+fn tag_name(tag: &[u8]) -> &[u8] {
+    let name = &tag[1..];
+    let end = name
+        .iter()
+        .position(|&b| b == b'/' || b == b'>' || (b as char).is_whitespace())
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markup_len() {
+        assert_eq!(markup_len(b"<a b='>'>text"), Some(9));
+        assert_eq!(markup_len(b"<!-- > -->"), Some(10));
+        assert_eq!(markup_len(b"<![CDATA[ <a> ]]>"), Some(17));
+        assert_eq!(markup_len(b"<!DOCTYPE a [<!ENTITY b '>'>]>"), Some(30));
+        assert_eq!(markup_len(b"<a b='"), None);
+        assert_eq!(markup_len(b"<!-"), None);
+    }
+}
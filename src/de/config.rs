@@ -0,0 +1,435 @@
+use std::any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::str;
+
+use serde::de::{self, Unexpected};
+use xml::reader::{EventReader, ParserConfig, ParserConfig2};
+
+use de::{Deserializer, LimitedReader, SliceSource};
+use element::attribute_field;
+use error::{ErrorKind, Result};
+use super::html;
+use super::number::Number;
+
+/// Options that control how a `Deserializer` maps XML onto Serde's data model.
+///
+/// ```rust
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::DeserializerConfig;
+/// # fn main() {
+/// let s = r##"<item name="hello" />"##;
+/// let mut de = DeserializerConfig::new()
+///     .prefix_attributes(true)
+///     .create_deserializer(s.as_bytes());
+/// # let _ = &mut de;
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DeserializerConfig {
+    prefix_attributes: bool,
+    bool_yes_no: bool,
+    bool_on_off: bool,
+    bool_case_insensitive: bool,
+    pub(crate) empty_attributes_as_none: bool,
+    pub(crate) xs_lists: bool,
+    pub(crate) interleaved_sequences: bool,
+    numbers_trim_whitespace: bool,
+    numbers_hex: bool,
+    numbers_thousands_separator: Option<char>,
+    pub(crate) limits: Limits,
+    entities: BTreeMap<String, String>,
+    html_entities: bool,
+    pub(crate) internal_dtd: bool,
+}
+
+/// Upper bounds on what a `Deserializer` will read, to protect against
+/// untrusted input.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Limits {
+    pub depth: Option<usize>,
+    pub attributes: Option<usize>,
+    pub text_length: Option<usize>,
+    pub document_size: Option<usize>,
+    pub elements: Option<usize>,
+    pub entity_expansion_depth: Option<u8>,
+    pub entity_expansion_length: Option<usize>,
+}
+
+impl Limits {
+    pub fn check_depth(&self, depth: usize) -> Result<()> {
+        match self.depth {
+            Some(max) if depth > max => Err(ErrorKind::DepthLimitExceeded(max).into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_text_length(&self, text: &str) -> Result<()> {
+        match self.text_length {
+            Some(max) if text.len() > max => Err(ErrorKind::TextLengthLimitExceeded(max).into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl DeserializerConfig {
+    /// Creates a config with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key attributes as `@name`, the way attribute fields are named, when
+    /// an element is read through `deserialize_any`. This tells `<a id="1"/>`
+    /// apart from `<a><id>1</id></a>`, in untagged enums for instance.
+    ///
+    /// Off by default, so that buffered values (untagged enums,
+    /// `#[serde(flatten)]`) still match struct fields by their plain names.
+    pub fn prefix_attributes(mut self, enabled: bool) -> Self {
+        self.prefix_attributes = enabled;
+        self
+    }
+
+    /// Also accept `yes` and `no` as booleans, in attributes and elements.
+    ///
+    /// By default only the `xs:boolean` forms `true`, `false`, `1` and `0`
+    /// are accepted.
+    pub fn bool_yes_no(mut self, enabled: bool) -> Self {
+        self.bool_yes_no = enabled;
+        self
+    }
+
+    /// Also accept `on` and `off` as booleans, in attributes and elements.
+    pub fn bool_on_off(mut self, enabled: bool) -> Self {
+        self.bool_on_off = enabled;
+        self
+    }
+
+    /// Accept booleans in any case, e.g. `True` or `NO`.
+    pub fn bool_case_insensitive(mut self, enabled: bool) -> Self {
+        self.bool_case_insensitive = enabled;
+        self
+    }
+
+    /// Deserialize empty attributes such as `discount=""` into `None` when
+    /// they're read into an `Option`, rather than into `Some` of an empty
+    /// value. Missing attributes are always `None`.
+    pub fn empty_attributes_as_none(mut self, enabled: bool) -> Self {
+        self.empty_attributes_as_none = enabled;
+        self
+    }
+
+    /// Deserialize sequences and tuples in attributes and in elements holding
+    /// text from whitespace-separated items, as XML Schema's `xs:list` types
+    /// are written: `refs="a b c"` or `<coords>1.5 2.0</coords>`. Only
+    /// repeated elements that hold other elements are still read as one
+    /// sequence item each.
+    ///
+    /// The `List` wrapper type does the same for a single field.
+    pub fn xs_lists(mut self, enabled: bool) -> Self {
+        self.xs_lists = enabled;
+        self
+    }
+
+    /// Collect all children of an element with the same name into a `Vec`
+    /// field, even when other elements come between them, as in
+    /// `<a/><b/><a/>`. By default a sequence ends at the first sibling with
+    /// another name, and a later `<a/>` is a duplicate field.
+    pub fn interleaved_sequences(mut self, enabled: bool) -> Self {
+        self.interleaved_sequences = enabled;
+        self
+    }
+
+    /// Accept numbers with whitespace around them, such as `" 42 "` in an
+    /// attribute. Element text is already trimmed.
+    ///
+    /// A leading `+` is always accepted, as XML Schema allows.
+    pub fn numbers_trim_whitespace(mut self, enabled: bool) -> Self {
+        self.numbers_trim_whitespace = enabled;
+        self
+    }
+
+    /// Accept integers written in hex with a `0x` prefix, such as `0x1F`.
+    pub fn numbers_hex(mut self, enabled: bool) -> Self {
+        self.numbers_hex = enabled;
+        self
+    }
+
+    /// Accept `separator` between groups of three digits in the integer part
+    /// of numbers, such as the commas in `1,000,000`. Numbers grouped any
+    /// other way, like `1,2,3`, fail to parse.
+    pub fn numbers_thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.numbers_thousands_separator = separator;
+        self
+    }
+
+    /// Fail with `ErrorKind::DepthLimitExceeded` on elements nested more
+    /// than `max` levels deep, counting the root element as the first.
+    ///
+    /// This and the other limits are unset by default. Set them all when
+    /// reading untrusted input.
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.limits.depth = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::AttributeLimitExceeded` on elements with more
+    /// than `max` attributes.
+    pub fn max_attributes(mut self, max: usize) -> Self {
+        self.limits.attributes = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::TextLengthLimitExceeded` on text, CDATA
+    /// sections or comments longer than `max` bytes. Names and attribute
+    /// values are held to the same limit.
+    ///
+    /// The parser reports going past this limit and `max_attributes` with
+    /// the same error, so when both are set it fails with
+    /// `ErrorKind::Syntax` instead.
+    pub fn max_text_length(mut self, max: usize) -> Self {
+        self.limits.text_length = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::DocumentSizeLimitExceeded` once more than `max`
+    /// bytes of the document have been read. The bytes are counted by the
+    /// `LimitedReader` that `create_deserializer` wraps the reader in.
+    pub fn max_document_size(mut self, max: usize) -> Self {
+        self.limits.document_size = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::ElementLimitExceeded` on documents with more
+    /// than `max` elements.
+    pub fn max_elements(mut self, max: usize) -> Self {
+        self.limits.elements = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::EntityExpansionLimitExceeded` on entities that
+    /// expand into other entities more than `max` levels deep. Defaults to 10.
+    pub fn max_entity_expansion_depth(mut self, max: u8) -> Self {
+        self.limits.entity_expansion_depth = Some(max);
+        self
+    }
+
+    /// Fail with `ErrorKind::EntityExpansionLimitExceeded` on entities that
+    /// expand into more than `max` bytes. Defaults to 1,000,000.
+    pub fn max_entity_expansion_length(mut self, max: usize) -> Self {
+        self.limits.entity_expansion_length = Some(max);
+        self
+    }
+
+    /// Supply the default and `#FIXED` attribute values that the internal
+    /// DTD subset declares with `<!ATTLIST>`, as if the start tags had them.
+    ///
+    /// The entities the subset declares with `<!ENTITY>` are expanded
+    /// whether this is set or not, within the entity expansion limits.
+    pub fn internal_dtd(mut self, enabled: bool) -> Self {
+        self.internal_dtd = enabled;
+        self
+    }
+
+    /// Expand the entity `&name;` to `value`, in text and attribute values.
+    ///
+    /// Without this, entities other than the five XML predefines are an
+    /// error. Entities set here take precedence over `html_entities`.
+    pub fn entity<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.entities.insert(name.into(), value.into());
+        self
+    }
+
+    /// Expand all the entities in `entities`, as `entity` does for one.
+    pub fn entities<I, N, V>(mut self, entities: I) -> Self
+    where
+        I: IntoIterator<Item = (N, V)>,
+        N: Into<String>,
+        V: Into<String>,
+    {
+        for (name, value) in entities {
+            self.entities.insert(name.into(), value.into());
+        }
+        self
+    }
+
+    /// Expand the named character references of HTML 4, such as `&nbsp;`,
+    /// `&copy;` or `&eacute;`.
+    pub fn html_entities(mut self, enabled: bool) -> Self {
+        self.html_entities = enabled;
+        self
+    }
+
+    /// Creates a `Deserializer` reading from `reader` with these settings and
+    /// the same parser configuration as `Deserializer::new_from_reader`.
+    pub fn create_deserializer<R: Read>(self, reader: R) -> Deserializer<LimitedReader<R>> {
+        let reader = LimitedReader::new(reader, self.limits.document_size);
+        self.create_unlimited_deserializer(reader)
+    }
+
+    /// Creates a `Deserializer` for an in-memory document, which can borrow
+    /// strings from `input` as long as it is UTF-8. See `from_slice`.
+    pub fn create_slice_deserializer<'de>(
+        self,
+        input: &'de [u8],
+    ) -> Deserializer<LimitedReader<&'de [u8]>, SliceSource<'de>> {
+        let reader = LimitedReader::new(input, self.limits.document_size);
+        self.create_unlimited_slice_deserializer(reader, input)
+    }
+
+    /// Like `create_deserializer`, but without counting the bytes read for
+    /// `max_document_size`.
+    pub(crate) fn create_unlimited_deserializer<R: Read>(self, reader: R) -> Deserializer<R> {
+        let reader = EventReader::new_with_config(reader, self.parser_config());
+        let mut de = Deserializer::new_with_config(reader, self);
+        de.trim_text = true;
+        de
+    }
+
+    pub(crate) fn create_unlimited_slice_deserializer<'de, R: Read>(
+        self,
+        reader: R,
+        input: &'de [u8],
+    ) -> Deserializer<R, SliceSource<'de>> {
+        let reader = EventReader::new_with_config(reader, self.parser_config());
+        // Positions in other encodings don't match byte offsets in `input`,
+        // so an empty source makes all their text owned
+        let source = SliceSource::new(str::from_utf8(input).unwrap_or(""));
+        let mut de = Deserializer::new_with_source(reader, self, source);
+        de.trim_text = true;
+        de
+    }
+
+    /// Comments and CDATA sections are kept for `Element`, and text is only
+    /// trimmed once `Deserializer` has merged it with them.
+    ///
+    /// The parser fails as soon as it reads past the attribute and text
+    /// limits, rather than after it has read the whole tag or text.
+    pub(crate) fn parser_config(&self) -> ParserConfig2 {
+        let mut config = ParserConfig::new()
+            .trim_whitespace(false)
+            .whitespace_to_characters(true)
+            .cdata_to_characters(false)
+            .ignore_comments(false)
+            .coalesce_characters(true);
+        config.extra_entities = self.extra_entities();
+        let mut config = ParserConfig2::from(config);
+        if let Some(max) = self.limits.entity_expansion_depth {
+            config = config.max_entity_expansion_depth(max);
+        }
+        if let Some(max) = self.limits.entity_expansion_length {
+            config = config.max_entity_expansion_length(max);
+        }
+        // Stop the parser from buffering more than the limits allow
+        if let Some(max) = self.limits.attributes {
+            config = config.max_attributes(max);
+        }
+        if let Some(max) = self.limits.text_length {
+            config = config
+                .max_data_length(max)
+                .max_attribute_length(max)
+                .max_name_length(max);
+        }
+        config
+    }
+
+    /// The entities to expand besides the predefined ones and those of the
+    /// internal DTD subset.
+    pub(crate) fn extra_entities(&self) -> HashMap<String, String> {
+        let mut entities = HashMap::new();
+        if self.html_entities {
+            for &(name, value) in html::ENTITIES {
+                entities.insert(name.to_string(), value.to_string());
+            }
+        }
+        for (name, value) in &self.entities {
+            entities.insert(name.clone(), value.clone());
+        }
+        entities
+    }
+
+    pub(crate) fn parse_bool(&self, text: &str) -> Result<bool> {
+        let mut words = vec![("true", true), ("false", false), ("1", true), ("0", false)];
+        if self.bool_yes_no {
+            words.extend(vec![("yes", true), ("no", false)]);
+        }
+        if self.bool_on_off {
+            words.extend(vec![("on", true), ("off", false)]);
+        }
+        let text = text.trim();
+        let value = words.iter().find(|&&(word, _)| {
+            if self.bool_case_insensitive {
+                word.eq_ignore_ascii_case(text)
+            } else {
+                word == text
+            }
+        });
+        match value {
+            Some(&(_, value)) => Ok(value),
+            None => {
+                let words = words.iter().map(|&(word, _)| word).collect::<Vec<_>>();
+                let expected = format!("a boolean ({})", words.join(", "));
+                Err(de::Error::invalid_value(Unexpected::Str(text), &expected.as_str()))
+            },
+        }
+    }
+
+    pub(crate) fn parse_number<N: Number>(&self, text: &str) -> Result<N> {
+        let mut number = Cow::Borrowed(text);
+        if self.numbers_trim_whitespace {
+            number = Cow::Borrowed(text.trim());
+        }
+        if let Some(separator) = self.numbers_thousands_separator {
+            if number.contains(separator) {
+                number = Cow::Owned(remove_separators(&number, separator));
+            }
+        }
+        let (sign, digits) = match number.find(|c| c != '+' && c != '-') {
+            Some(start) => number.split_at(start),
+            None => ("", &*number),
+        };
+        let hex = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X"));
+        let value = match hex {
+            Some(hex) if self.numbers_hex && sign.len() <= 1 => {
+                N::parse_hex(&format!("{}{}", sign, hex))
+            },
+            _ => N::parse_decimal(&number),
+        };
+        value.ok_or_else(|| {
+            ErrorKind::InvalidNumber(any::type_name::<N>().to_string(), text.to_string()).into()
+        })
+    }
+
+    pub(crate) fn prefixed_attribute(&self, name: &str) -> String {
+        if self.prefix_attributes {
+            attribute_field(name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// Removes the separators from runs of digits grouped by threes, such as
+/// `12,345`. Runs grouped any other way, or following a decimal point, are
+/// kept as they are for the number to fail to parse.
+fn remove_separators(number: &str, separator: char) -> String {
+    let mut result = String::with_capacity(number.len());
+    let mut rest = number;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != separator).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        let mut groups = run.split(separator);
+        let first = groups.next().unwrap_or("");
+        let grouped = (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3);
+        if grouped && !result.ends_with('.') {
+            result.extend(run.chars().filter(|&c| c != separator));
+        } else {
+            result.push_str(run);
+        }
+        rest = tail;
+    }
+    result.push_str(rest);
+    result
+}
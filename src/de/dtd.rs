@@ -0,0 +1,411 @@
+//! The attribute defaults of an internal DTD subset, for
+//! `DeserializerConfig::internal_dtd`.
+
+use std::collections::HashMap;
+
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::Namespace;
+
+use error::{ErrorKind, Result};
+
+/// How deep and long entities may expand when the config sets no limit, the
+/// same as xml-rs uses for the entities in text.
+pub const DEFAULT_EXPANSION_DEPTH: u8 = 10;
+pub const DEFAULT_EXPANSION_LENGTH: usize = 1_000_000;
+
+/// The default and `#FIXED` attribute values declared with `<!ATTLIST>`, by
+/// element name.
+#[derive(Debug, Default)]
+pub struct AttributeDefaults {
+    elements: HashMap<String, Vec<(String, String)>>,
+}
+
+struct Declaration {
+    name: String,
+    cdata: bool,
+    value: String,
+}
+
+impl AttributeDefaults {
+    /// Collects the attribute defaults of `doctype`, the text of a
+    /// `<!DOCTYPE>` declaration.
+    ///
+    /// References in the values are expanded from `extra_entities` first and
+    /// the `<!ENTITY>` declarations of the subset second.
+    pub fn parse(
+        doctype: &str,
+        extra_entities: &HashMap<String, String>,
+        max_depth: u8,
+        max_length: usize,
+    ) -> Result<Self> {
+        let mut scanner = Scanner {
+            rest: internal_subset(doctype),
+        };
+        let mut entities = HashMap::new();
+        let mut attlists = Vec::new();
+        while !scanner.rest.is_empty() && !scanner.rest.starts_with(']') {
+            if scanner.eat("<!--") {
+                scanner.skip_past("-->");
+            } else if scanner.eat("<?") {
+                scanner.skip_past("?>");
+            } else if scanner.eat("<!ENTITY") {
+                if let Some((name, value)) = scanner.entity() {
+                    // The first declaration of an entity is binding
+                    entities.entry(name).or_insert(value);
+                }
+                scanner.skip_declaration();
+            } else if scanner.eat("<!ATTLIST") {
+                if let Some(attlist) = scanner.attlist() {
+                    attlists.push(attlist);
+                }
+                scanner.skip_declaration();
+            } else if scanner.eat("<!") {
+                scanner.skip_declaration();
+            } else {
+                scanner.advance(1);
+            }
+        }
+
+        let expander = Expander {
+            extra_entities,
+            entities: &entities,
+            max_depth,
+            max_length,
+        };
+        let mut defaults = AttributeDefaults::default();
+        for (element, declarations) in attlists {
+            let attributes = defaults.elements.entry(element).or_default();
+            for declaration in declarations {
+                // The first declaration of an attribute is binding, too
+                if attributes.iter().any(|(name, _)| *name == declaration.name) {
+                    continue;
+                }
+                let mut value = String::new();
+                expander.expand(&declaration.value, 0, &mut value)?;
+                if !declaration.cdata {
+                    value = value.split(' ').filter(|token| !token.is_empty()).collect::<Vec<_>>().join(" ");
+                }
+                attributes.push((declaration.name, value));
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// Adds the declared attributes that the start tag of `name` leaves out
+    /// to `attributes`.
+    pub fn apply(&self, name: &OwnedName, attributes: &mut Vec<OwnedAttribute>, namespace: &Namespace) {
+        let declared = match self.elements.get(&name.borrow().to_repr()) {
+            Some(declared) => declared,
+            None => return,
+        };
+        for (qualified, value) in declared {
+            let mut name: OwnedName = match qualified.parse() {
+                Ok(name) => name,
+                Err(()) => continue,
+            };
+            if name.prefix_ref() == Some("xmlns") || name.local_name == "xmlns" && name.prefix.is_none() {
+                continue;
+            }
+            if let Some(ref prefix) = name.prefix {
+                name.namespace = namespace.get(prefix).map(str::to_string);
+            }
+            let present = attributes.iter().any(|attribute| {
+                attribute.name.local_name == name.local_name && attribute.name.prefix == name.prefix
+            });
+            if !present {
+                attributes.push(OwnedAttribute::new(name, value.clone()));
+            }
+        }
+    }
+}
+
+/// Returns what follows the `[` that opens the internal subset of
+/// `<!DOCTYPE root SYSTEM "root.dtd" [ ... ]>`, if there is one.
+fn internal_subset(doctype: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in doctype.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') => return &doctype[i + 1..],
+            _ => {},
+        }
+    }
+    ""
+}
+
+struct Scanner<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    fn advance(&mut self, bytes: usize) {
+        let mut end = bytes.min(self.rest.len());
+        while !self.rest.is_char_boundary(end) {
+            end += 1;
+        }
+        self.rest = &self.rest[end..];
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest.starts_with(prefix) {
+            self.rest = &self.rest[prefix.len()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_past(&mut self, end: &str) {
+        match self.rest.find(end) {
+            Some(i) => self.rest = &self.rest[i + end.len()..],
+            None => self.rest = "",
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start_matches(is_whitespace);
+    }
+
+    /// Skips to the end of the current declaration, past any quoted `>`.
+    fn skip_declaration(&mut self) {
+        while let Some(c) = self.rest.chars().next() {
+            match c {
+                '>' => {
+                    self.advance(1);
+                    return;
+                },
+                '"' | '\'' => {
+                    if self.quoted().is_none() {
+                        self.rest = "";
+                    }
+                },
+                _ => self.advance(c.len_utf8()),
+            }
+        }
+    }
+
+    fn name(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| is_whitespace(c) || "<>()|\"'%;".contains(c))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return None;
+        }
+        let name = &self.rest[..end];
+        self.rest = &self.rest[end..];
+        Some(name)
+    }
+
+    fn quoted(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let quote = self.rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let end = self.rest[1..].find(quote)?;
+        let value = &self.rest[1..=end];
+        self.rest = &self.rest[end + 2..];
+        Some(value)
+    }
+
+    /// Reads the name and literal value of a general entity. Parameter and
+    /// external entities have no value that could be used in attributes.
+    fn entity(&mut self) -> Option<(String, String)> {
+        self.skip_whitespace();
+        if self.rest.starts_with('%') {
+            return None;
+        }
+        let name = self.name()?;
+        let value = self.quoted()?;
+        Some((name.to_string(), value.to_string()))
+    }
+
+    /// Reads an element name and its attribute declarations that have a
+    /// default value.
+    fn attlist(&mut self) -> Option<(String, Vec<Declaration>)> {
+        let element = self.name()?.to_string();
+        let mut declarations = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.rest.is_empty() || self.rest.starts_with('>') {
+                break;
+            }
+            let name = self.name()?.to_string();
+            let cdata = self.attribute_type()? == "CDATA";
+            self.skip_whitespace();
+            if self.eat("#REQUIRED") || self.eat("#IMPLIED") {
+                continue;
+            }
+            self.eat("#FIXED");
+            let value = self.quoted()?.to_string();
+            declarations.push(Declaration { name, cdata, value });
+        }
+        Some((element, declarations))
+    }
+
+    /// Reads a type such as `CDATA`, `(yes|no)` or `NOTATION (a|b)`.
+    fn attribute_type(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let name = if self.rest.starts_with('(') {
+            "ENUMERATION"
+        } else {
+            self.name()?
+        };
+        if name == "NOTATION" || name == "ENUMERATION" {
+            self.skip_whitespace();
+            if !self.eat("(") {
+                return None;
+            }
+            self.skip_past(")");
+        }
+        Some(name)
+    }
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\r' || c == '\n'
+}
+
+struct Expander<'a> {
+    extra_entities: &'a HashMap<String, String>,
+    entities: &'a HashMap<String, String>,
+    max_depth: u8,
+    max_length: usize,
+}
+
+impl<'a> Expander<'a> {
+    /// Appends `value` to `out` with references expanded and whitespace
+    /// normalized, as in attribute values.
+    fn expand(&self, value: &str, depth: u8, out: &mut String) -> Result<()> {
+        if depth > self.max_depth {
+            return Err(ErrorKind::EntityExpansionLimitExceeded.into());
+        }
+        let mut rest = value;
+        while let Some(start) = rest.find('&') {
+            push_normalized(out, &rest[..start]);
+            let end = match rest[start..].find(';') {
+                Some(end) => start + end,
+                None => return Err(undefined(&rest[start..])),
+            };
+            let name = &rest[start + 1..end];
+            if let Some(c) = character_reference(name) {
+                out.push(c);
+            } else if let Some(c) = predefined(name) {
+                out.push(c);
+            } else if let Some(value) = self.extra_entities.get(name) {
+                out.push_str(value);
+            } else if let Some(value) = self.entities.get(name) {
+                self.expand(value, depth + 1, out)?;
+            } else {
+                return Err(undefined(&rest[start..=end]));
+            }
+            if out.len() > self.max_length {
+                return Err(ErrorKind::EntityExpansionLimitExceeded.into());
+            }
+            rest = &rest[end + 1..];
+        }
+        push_normalized(out, rest);
+        Ok(())
+    }
+}
+
+fn push_normalized(out: &mut String, text: &str) {
+    out.extend(text.chars().map(|c| if is_whitespace(c) { ' ' } else { c }));
+}
+
+fn character_reference(name: &str) -> Option<char> {
+    let code = if let Some(hex) = name.strip_prefix("#x") {
+        u32::from_str_radix(hex, 16).ok()?
+    } else {
+        name.strip_prefix('#')?.parse().ok()?
+    };
+    ::std::char::from_u32(code)
+}
+
+fn predefined(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => None,
+    }
+}
+
+fn undefined(reference: &str) -> ::error::Error {
+    ErrorKind::Custom(format!("undefined entity '{}' in a DTD attribute default", reference)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(doctype: &str) -> Result<AttributeDefaults> {
+        AttributeDefaults::parse(doctype, &HashMap::new(), DEFAULT_EXPANSION_DEPTH, 100)
+    }
+
+    fn defaults(doctype: &str, element: &str) -> Vec<(String, String)> {
+        parse(doctype).unwrap().elements.remove(element).unwrap_or_default()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_attlist() {
+        let doctype = r#"<!DOCTYPE doc [
+            <!-- <!ATTLIST doc commented CDATA "no"> -->
+            <!ATTLIST doc
+                version CDATA #FIXED "1.0"
+                lang NMTOKEN "  en  "
+                id ID #REQUIRED
+                note CDATA #IMPLIED
+                kind (a|b) "b"
+                format NOTATION (gif|png) 'png'>
+            <!ATTLIST doc version CDATA "2.0" title CDATA "a > b">
+            <!ELEMENT doc (#PCDATA)>
+        ]>"#;
+        assert_eq!(
+            defaults(doctype, "doc"),
+            pairs(&[
+                ("version", "1.0"),
+                ("lang", "en"),
+                ("kind", "b"),
+                ("format", "png"),
+                ("title", "a > b"),
+            ])
+        );
+        assert!(defaults(r#"<!DOCTYPE doc SYSTEM "doc.dtd">"#, "doc").is_empty());
+    }
+
+    #[test]
+    fn test_entities() {
+        let doctype = r#"<!DOCTYPE doc [
+            <!ENTITY owner "ACME &amp; Co.">
+            <!ENTITY label "&owner;&#x20;&#169;">
+            <!ENTITY owner "ignored">
+            <!ATTLIST doc label CDATA "&label;	tab">
+        ]>"#;
+        assert_eq!(
+            defaults(doctype, "doc"),
+            pairs(&[("label", "ACME & Co. \u{a9} tab")])
+        );
+
+        let undefined = r#"<!DOCTYPE doc [<!ATTLIST doc a CDATA "&nope;">]>"#;
+        assert!(parse(undefined).is_err());
+
+        let laughs = r#"<!DOCTYPE doc [
+            <!ENTITY a "&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;">
+            <!ENTITY b "&c;&c;&c;&c;&c;&c;&c;&c;&c;&c;">
+            <!ENTITY c "lol">
+            <!ATTLIST doc a CDATA "&a;">
+        ]>"#;
+        match *parse(laughs).unwrap_err().kind() {
+            ErrorKind::EntityExpansionLimitExceeded => {},
+            ref other => panic!("{:?}", other),
+        }
+    }
+}
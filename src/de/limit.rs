@@ -0,0 +1,55 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+
+use error::{Error, ErrorKind};
+
+/// A reader that fails once more than a given number of bytes have been read
+/// from it, so that `DeserializerConfig::max_document_size` holds before the
+/// parser gets to buffer anything.
+pub struct LimitedReader<R> {
+    inner: R,
+    read: usize,
+    max: Option<usize>,
+}
+
+impl<R: Read> LimitedReader<R> {
+    pub(crate) fn new(inner: R, max: Option<usize>) -> Self {
+        LimitedReader { inner, read: 0, max }
+    }
+
+    /// Returns the reader this one wraps.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.read += len;
+        match self.max {
+            Some(max) if self.read > max => Err(io::Error::other(TooLarge(max))),
+            _ => Ok(len),
+        }
+    }
+}
+
+/// Carries the limit through the parser, which reports read errors as they
+/// are.
+#[derive(Debug)]
+struct TooLarge(usize);
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "document larger than {} bytes", self.0)
+    }
+}
+
+impl error::Error for TooLarge {}
+
+/// Recognizes the error `LimitedReader` failed with.
+pub(crate) fn size_limit_error(error: &io::Error) -> Option<Error> {
+    let inner = error.get_ref()?.downcast_ref::<TooLarge>()?;
+    Some(ErrorKind::DocumentSizeLimitExceeded(inner.0).into())
+}
@@ -0,0 +1,80 @@
+use serde::de;
+
+use de::DeserializerConfig;
+use error::{Error, ErrorKind, Result};
+use super::map::AttrValueDeserializer;
+
+/// Deserializes the items of a whitespace-separated list, each the way an
+/// attribute value would be.
+pub struct ListDeserializer<'a> {
+    text: String,
+    config: &'a DeserializerConfig,
+}
+
+impl<'a> ListDeserializer<'a> {
+    pub fn new(text: String, config: &'a DeserializerConfig) -> Self {
+        ListDeserializer { text, config }
+    }
+
+    fn visit_items<'de, V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut items = ListAccess {
+            items: self.text.split_whitespace(),
+            config: self.config,
+        };
+        let value = visitor.visit_seq(&mut items)?;
+        match items.items.next() {
+            Some(_) => {
+                let message = format!("too many items in the list '{}'", self.text);
+                Err(ErrorKind::Custom(message).into())
+            },
+            None => Ok(value),
+        }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ListDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.visit_items(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ListAccess<'a, 't> {
+    items: ::std::str::SplitWhitespace<'t>,
+    config: &'a DeserializerConfig,
+}
+
+impl<'de, 'a, 't> de::SeqAccess<'de> for ListAccess<'a, 't> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        match self.items.next() {
+            Some(item) => {
+                seed.deserialize(AttrValueDeserializer::new(item.to_string(), self.config))
+                    .map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+}
@@ -1,58 +1,200 @@
 use std::io::Read;
+use std::ops::Range;
 
-use serde::de::{self, IntoDeserializer};
+use serde::de::{self, value::MapDeserializer, IntoDeserializer};
 use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
 use xml::reader::XmlEvent;
 
 use Deserializer;
+use de::DeserializerConfig;
+use element::{attribute_field, not_an_element, visit_element, Element, Node, ELEMENT_TOKEN};
 use error::{Error, Result};
+use list::LIST_TOKEN;
+use super::list::ListDeserializer;
+use super::source::Source;
 
-pub struct MapAccess<'a, R: 'a + Read> {
-    attrs: ::std::vec::IntoIter<OwnedAttribute>,
-    next_value: Option<String>,
-    de: &'a mut Deserializer<R>,
+pub struct MapAccess<'a, R: 'a + Read, S: 'a> {
+    attrs: ::std::vec::IntoIter<(OwnedAttribute, Option<Range<usize>>)>,
+    next_value: Option<(String, Option<Range<usize>>)>,
+    de: &'a mut Deserializer<R, S>,
+    fields: &'static [&'static str],
+    // The fields attributes go by, those of the struct variant for maps
+    attribute_fields: &'static [&'static str],
     inner_value: bool,
+    // What no field takes, while there is a `$other` field to hand it to
+    other: Option<Element>,
+    next_other: Option<Element>,
+    // Every attribute, while there is a `$attrs` field to hand them to
+    all_attrs: Option<Vec<(String, String)>>,
+    next_all_attrs: Option<Vec<(String, String)>>,
 }
 
-impl<'a, R: 'a + Read> MapAccess<'a, R> {
-    pub fn new(de: &'a mut Deserializer<R>, attrs: Vec<OwnedAttribute>, inner_value: bool) -> Self {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> MapAccess<'a, R, S> {
+    /// Must be called right after reading the start tag of `name` that
+    /// `attrs` belong to.
+    ///
+    /// `fields` are those of the struct being deserialized, if any.
+    pub fn new(
+        de: &'a mut Deserializer<R, S>,
+        name: &OwnedName,
+        attrs: Vec<OwnedAttribute>,
+        fields: &'static [&'static str],
+    ) -> Self {
+        let spans = de.source.attribute_spans(de.position, &attrs);
+        let other = if fields.contains(&"$other") {
+            let mut other = Element::new(&name.local_name);
+            other.name = name.clone();
+            Some(other)
+        } else {
+            None
+        };
+        let all_attrs = if fields.contains(&"$attrs") {
+            Some(qualified_attributes(&attrs))
+        } else {
+            None
+        };
         MapAccess {
-            attrs: attrs.into_iter(),
+            attrs: attrs.into_iter().zip(spans).collect::<Vec<_>>().into_iter(),
             next_value: None,
             de: de,
-            inner_value: inner_value,
+            fields,
+            attribute_fields: fields,
+            inner_value: fields.contains(&"$value"),
+            other,
+            next_other: None,
+            all_attrs,
+            next_all_attrs: None,
         }
     }
+
+    /// Names attributes after the `fields` of a struct variant, which is
+    /// otherwise read as a map.
+    pub fn with_attribute_fields(mut self, fields: &'static [&'static str]) -> Self {
+        self.attribute_fields = fields;
+        self
+    }
+
+    /// Whether a child element called `name` goes to `$other` rather than to
+    /// a field of its own.
+    fn is_other_child(&self, name: &str) -> bool {
+        self.other.is_some() && !self.inner_value && !self.fields.contains(&name)
+    }
 }
 
-impl<'de, 'a, R: 'a + Read> de::MapAccess<'de> for MapAccess<'a, R> {
+/// Keys attributes by their qualified names, such as `xlink:href`, adding
+/// the `xmlns:xlink` declarations that writing them back needs.
+fn qualified_attributes(attrs: &[OwnedAttribute]) -> Vec<(String, String)> {
+    let mut qualified = Vec::new();
+    for attribute in attrs {
+        if let (Some(prefix), Some(uri)) = (&attribute.name.prefix, &attribute.name.namespace) {
+            let declaration = format!("xmlns:{}", prefix);
+            if !qualified.iter().any(|(name, _)| *name == declaration) {
+                qualified.push((declaration, uri.clone()));
+            }
+        }
+    }
+    for attribute in attrs {
+        qualified.push((attribute.name.borrow().to_repr(), attribute.value.clone()));
+    }
+    qualified
+}
+
+/// Fields renamed to `@name` only take the attribute `name`, the same way
+/// the serializer writes them. Maps, which structs with flattened fields are
+/// read as, have no fields to go by and get every attribute that way.
+fn attribute_key(fields: &[&str], name: String) -> String {
+    let prefixed = attribute_field(&name);
+    if fields.is_empty() || fields.contains(&prefixed.as_str()) {
+        prefixed
+    } else {
+        name
+    }
+}
+
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> de::MapAccess<'de> for MapAccess<'a, R, S> {
     type Error = Error;
 
     fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
         debug_assert_eq!(self.next_value, None);
-        match self.attrs.next() {
-            Some(OwnedAttribute { name, value }) => {
-                self.next_value = Some(value);
-                seed.deserialize(name.local_name.into_deserializer())
-                    .map(Some)
-            },
-            None => match *self.de.peek()? {
-                XmlEvent::StartElement { ref name, .. } => seed.deserialize(
-                    if !self.inner_value {
-                        name.local_name.as_str()
+        for (attribute, span) in self.attrs.by_ref() {
+            let key = attribute_key(self.attribute_fields, attribute.name.local_name.clone());
+            match self.other {
+                // With `$attrs` there, it gets the attributes without a field
+                Some(_) if self.all_attrs.is_some() && !self.fields.contains(&key.as_str()) => {},
+                Some(ref mut other) if !self.fields.contains(&key.as_str()) => {
+                    other.attributes.push(attribute);
+                },
+                _ => {
+                    self.next_value = Some((attribute.value, span));
+                    return seed.deserialize(key.into_deserializer()).map(Some);
+                },
+            }
+        }
+        if let Some(all_attrs) = self.all_attrs.take() {
+            self.next_all_attrs = Some(all_attrs);
+            return seed.deserialize("$attrs".into_deserializer()).map(Some);
+        }
+        loop {
+            let key = match *self.de.peek()? {
+                XmlEvent::StartElement { ref name, .. } if !self.inner_value => {
+                    Some(name.local_name.clone())
+                },
+                XmlEvent::StartElement { .. } | XmlEvent::Characters(_) => Some("$value".to_string()),
+                _ => None,
+            };
+            match key {
+                Some(key) if self.is_other_child(&key) => {
+                    let child = if key == "$value" {
+                        expect!(self.de.next()?, XmlEvent::Characters(text) => Ok(Node::Text(text)))?
                     } else {
-                        "$value"
-                    }.into_deserializer(),
-                ).map(Some),
-                XmlEvent::Characters(_) => seed.deserialize("$value".into_deserializer()).map(Some),
-                _ => Ok(None),
-            },
+                        Node::Element(self.de.read_element()?)
+                    };
+                    if let Some(ref mut other) = self.other {
+                        other.children.push(child);
+                    }
+                },
+                Some(key) => return seed.deserialize(key.into_deserializer()).map(Some),
+                None => {
+                    self.next_other = self.other.take();
+                    return match self.next_other {
+                        Some(_) => seed.deserialize("$other".into_deserializer()).map(Some),
+                        None => Ok(None),
+                    };
+                },
+            }
         }
     }
 
     fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        if let Some(all_attrs) = self.next_all_attrs.take() {
+            return seed.deserialize(MapDeserializer::<_, Error>::new(all_attrs.into_iter()));
+        }
+        if let Some(mut other) = self.next_other.take() {
+            // Declare the namespaces of the attributes, the children being
+            // trees that declare their own, less what that already does
+            for attribute in &other.attributes {
+                if let (Some(prefix), Some(uri)) = (&attribute.name.prefix, &attribute.name.namespace) {
+                    other.namespaces.insert(prefix.clone(), uri.clone());
+                }
+            }
+            let namespaces = &other.namespaces;
+            for child in &mut other.children {
+                if let Node::Element(ref mut child) = *child {
+                    child.namespaces.retain(|prefix, uri| namespaces.get(prefix) != Some(uri));
+                }
+            }
+            return seed.deserialize(OtherDeserializer(other));
+        }
         match self.next_value.take() {
-            Some(value) => seed.deserialize(AttrValueDeserializer(value)),
+            Some((value, span)) => {
+                let borrowed = span.map(|span| self.de.source.slice(span));
+                seed.deserialize(AttrValueDeserializer {
+                    value,
+                    borrowed,
+                    config: &self.de.config,
+                })
+            },
             None => {
                 if !self.inner_value {
                     if let XmlEvent::StartElement { .. } = *self.de.peek()? {
@@ -70,31 +212,114 @@ impl<'de, 'a, R: 'a + Read> de::MapAccess<'de> for MapAccess<'a, R> {
     }
 }
 
-struct AttrValueDeserializer(String);
+/// Hands the attributes and children no field takes to a `$other` field,
+/// which has to be an `Element`: anything else would lose their names.
+struct OtherDeserializer(Element);
+
+impl<'de> de::Deserializer<'de> for OtherDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        not_an_element()
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == ELEMENT_TOKEN {
+            visit_element(self.0, visitor)
+        } else {
+            not_an_element()
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+pub struct AttrValueDeserializer<'a, 'de> {
+    value: String,
+    // The value as it appears in the input, if it can be borrowed
+    borrowed: Option<&'de str>,
+    config: &'a DeserializerConfig,
+}
+
+impl<'a, 'de> AttrValueDeserializer<'a, 'de> {
+    /// Creates a deserializer for text that isn't borrowed from the input.
+    pub fn new(value: String, config: &'a DeserializerConfig) -> Self {
+        AttrValueDeserializer {
+            value,
+            borrowed: None,
+            config,
+        }
+    }
+
+    fn into_list(self) -> ListDeserializer<'a> {
+        ListDeserializer::new(self.value, self.config)
+    }
+}
 
 macro_rules! deserialize_type_attr {
     ($deserialize:ident => $visit:ident) => {
         fn $deserialize<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-            visitor.$visit(self.0.parse()?)
+            visitor.$visit(self.config.parse_number(&self.value)?)
         }
     }
 }
 
-impl<'de> de::Deserializer<'de> for AttrValueDeserializer {
+impl<'a, 'de> de::Deserializer<'de> for AttrValueDeserializer<'a, 'de> {
     type Error = Error;
 
     fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_string(self.0)
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.borrowed {
+            Some(borrowed) => visitor.visit_borrowed_str(borrowed),
+            None => visitor.visit_string(self.value),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
     }
 
     deserialize_type_attr!(deserialize_i8 => visit_i8);
     deserialize_type_attr!(deserialize_i16 => visit_i16);
     deserialize_type_attr!(deserialize_i32 => visit_i32);
     deserialize_type_attr!(deserialize_i64 => visit_i64);
+    deserialize_type_attr!(deserialize_i128 => visit_i128);
     deserialize_type_attr!(deserialize_u8 => visit_u8);
     deserialize_type_attr!(deserialize_u16 => visit_u16);
     deserialize_type_attr!(deserialize_u32 => visit_u32);
     deserialize_type_attr!(deserialize_u64 => visit_u64);
+    deserialize_type_attr!(deserialize_u128 => visit_u128);
     deserialize_type_attr!(deserialize_f32 => visit_f32);
     deserialize_type_attr!(deserialize_f64 => visit_f64);
 
@@ -104,19 +329,63 @@ impl<'de> de::Deserializer<'de> for AttrValueDeserializer {
         _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value> {
-        visitor.visit_enum(self.0.into_deserializer())
+        visitor.visit_enum(self.value.into_deserializer())
     }
 
     fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_some(self)
+        if self.value.is_empty() && self.config.empty_attributes_as_none {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
     fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_bool(!self.0.is_empty())
+        if self.value.is_empty() {
+            return visitor.visit_bool(false);
+        }
+        visitor.visit_bool(self.config.parse_bool(&self.value)?)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == LIST_TOKEN {
+            visitor.visit_newtype_struct(self.into_list())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.config.xs_lists {
+            de::Deserializer::deserialize_seq(self.into_list(), visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        if self.config.xs_lists {
+            de::Deserializer::deserialize_tuple(self.into_list(), len, visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_tuple(len, visitor)
     }
 
     forward_to_deserialize_any! {
-        char str string unit seq bytes map unit_struct newtype_struct tuple_struct
-        struct identifier tuple ignored_any byte_buf
+        unit map unit_struct struct ignored_any
     }
+
 }
@@ -1,16 +1,46 @@
-use std::io::Read;
+use std::collections::VecDeque;
+use std::io::{self, Read};
 
-use serde::de::{self, Unexpected};
-use xml::reader::{EventReader, ParserConfig, XmlEvent};
+use serde::de::{self, IntoDeserializer};
+use xml::common::{Position, TextPosition};
+use xml::reader::{EventReader, XmlEvent};
 use xml::name::OwnedName;
 
+use element::{self, visit_element, Element, Markup, Node, ELEMENT_TOKEN};
+use inner_xml::INNER_XML_TOKEN;
 use error::{Error, ErrorKind, Result};
+use list::LIST_TOKEN;
+use self::config::Limits;
+use self::dtd::AttributeDefaults;
+use self::list::ListDeserializer;
 use self::map::MapAccess;
 use self::seq::SeqAccess;
 use self::var::EnumAccess;
 
+pub use self::config::DeserializerConfig;
+pub use self::limit::LimitedReader;
+pub use self::source::{SliceSource, Source};
+pub use self::stream::StreamDeserializer;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use self::async_read::{ReadDocument, ReadItems};
+#[cfg(feature = "tokio")]
+pub use self::async_read::{from_tokio_reader, stream_from_tokio_reader, Tokio};
+#[cfg(feature = "futures")]
+pub use self::async_read::{from_futures_reader, stream_from_futures_reader, Futures};
+
+mod any;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_read;
+mod config;
+mod dtd;
+mod html;
+mod limit;
+mod list;
 mod map;
+mod number;
 mod seq;
+mod source;
+mod stream;
 mod var;
 
 /// A convenience method for deserialize some object from a string.
@@ -37,6 +67,42 @@ pub fn from_str<'de, T: de::Deserialize<'de>>(s: &str) -> Result<T> {
 }
 
 
+/// A convenience method for deserialize some object from an in-memory
+/// document, borrowing strings from it where possible.
+///
+/// `&str` fields (and `Cow<str>` fields marked `#[serde(borrow)]`) point into
+/// `input` if the text appears there verbatim. Text containing entities or
+/// CDATA sections can't be borrowed and fails to deserialize into `&str`.
+/// Nothing is borrowed from documents that aren't UTF-8, such as those
+/// declared `encoding="ISO-8859-1"`: they are decoded and read as usual, but
+/// all their text is owned. Unlike `from_str`, the result may borrow from the input, so pass
+/// `s.as_bytes()` to borrow from a string.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use std::borrow::Cow;
+/// # use serde_xml_rs::from_slice;
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Item<'a> {
+///     name: &'a str,
+///     #[serde(borrow)]
+///     source: Cow<'a, str>,
+/// }
+/// # fn main() {
+/// let s = br##"<item name="hello"><source>world &amp; more</source></item>"##;
+/// let item: Item = from_slice(s).unwrap();
+/// assert_eq!(item.name, "hello");
+/// assert_eq!(item.source, Cow::Owned::<str>("world & more".to_string()));
+/// # }
+/// ```
+pub fn from_slice<'de, T: de::Deserialize<'de>>(input: &'de [u8]) -> Result<T> {
+    T::deserialize(&mut Deserializer::new_from_slice(input))
+}
+
+
 /// A convenience method for deserialize some object from a reader.
 ///
 /// ```rust
@@ -60,32 +126,161 @@ pub fn from_reader<'de, R: Read, T: de::Deserialize<'de>>(reader: R) -> Result<T
     T::deserialize(&mut Deserializer::new_from_reader(reader))
 }
 
-pub struct Deserializer<R: Read> {
+/// A convenience method for deserialize only the first element matching
+/// `path` from a reader, skipping everything around it.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::from_reader_at;
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Quote {
+///     price: f64,
+/// }
+/// # fn main() {
+/// let s = r##"
+///     <Envelope>
+///         <Header><token>abc</token></Header>
+///         <Body><GetQuoteResponse><price>1.5</price></GetQuoteResponse></Body>
+///     </Envelope>
+/// "##;
+/// let quote: Quote = from_reader_at(s.as_bytes(), "/Envelope/Body/*").unwrap();
+/// assert_eq!(quote, Quote { price: 1.5 });
+/// # }
+/// ```
+pub fn from_reader_at<'de, R: Read, T: de::Deserialize<'de>>(reader: R, path: &str) -> Result<T> {
+    match Deserializer::new_from_reader(reader).into_stream_at(path)?.next() {
+        Some(result) => result,
+        None => Err(ErrorKind::PathNotFound(path.to_string()).into()),
+    }
+}
+
+pub struct Deserializer<R: Read, S = ()> {
     depth: usize,
     reader: EventReader<R>,
     peeked: Option<XmlEvent>,
     is_map_value: bool,
+    // Whether the peeked element is a newtype variant, whose content is
+    // the value rather than the element itself
+    is_variant_value: bool,
+    config: DeserializerConfig,
+    source: S,
+    position: TextPosition,
+    // Events to read before asking the parser again, with their positions:
+    // read ahead of `peeked` while merging text, or replayed from a tree
+    raw_peeked: VecDeque<(XmlEvent, TextPosition)>,
+    // Whether text is trimmed here rather than by the parser
+    trim_text: bool,
+    // Whether the last event read ended an element, which makes the next
+    // text a sibling of that element
+    after_end_element: bool,
+    // The depths of the elements being read as mixed content, where text
+    // that is all whitespace still separates sibling elements
+    mixed_depths: Vec<usize>,
+    // Elements parsed so far, for the configured limit
+    elements_read: usize,
+    // Read from the DOCTYPE at the root element with `config.internal_dtd`
+    attribute_defaults: Option<AttributeDefaults>,
 }
 
-impl<'de, R: Read> Deserializer<R> {
+impl<R: Read> Deserializer<R> {
     pub fn new(reader: EventReader<R>) -> Self {
+        Self::new_with_config(reader, DeserializerConfig::new())
+    }
+
+    pub fn new_with_config(reader: EventReader<R>, config: DeserializerConfig) -> Self {
+        Deserializer::new_with_source(reader, config, ())
+    }
+
+    pub fn new_from_reader(reader: R) -> Self {
+        // Nothing to count without a document size limit
+        DeserializerConfig::new().create_unlimited_deserializer(reader)
+    }
+}
+
+impl Deserializer<io::Empty> {
+    /// Creates a deserializer reading the events of `element` rather than
+    /// parsing a document.
+    pub fn new_from_element(element: Element) -> Self {
+        let mut de = Deserializer::new_from_reader(io::empty());
+        let position = TextPosition::new();
+        de.raw_peeked = element
+            .into_events()
+            .into_iter()
+            .chain(Some(XmlEvent::EndDocument))
+            .map(|event| (event, position))
+            .collect();
+        de
+    }
+}
+
+impl<'de> Deserializer<&'de [u8], SliceSource<'de>> {
+    pub fn new_from_slice(input: &'de [u8]) -> Self {
+        DeserializerConfig::new().create_unlimited_slice_deserializer(input, input)
+    }
+}
+
+impl<R: Read, S> Deserializer<R, S> {
+    /// Puts events set aside by `set_aside` back in front of the input.
+    fn replay(&mut self, events: Vec<(XmlEvent, TextPosition)>) {
+        if events.is_empty() {
+            return;
+        }
+        if let Some(peeked) = self.peeked.take() {
+            self.raw_peeked.push_front((peeked, self.position));
+        }
+        for event in events.into_iter().rev() {
+            self.raw_peeked.push_front(event);
+        }
+    }
+}
+
+impl<'de, R: Read, S: Source<'de>> Deserializer<R, S> {
+    /// Creates a deserializer that borrows strings from `source`, which must
+    /// hold the same document `reader` parses.
+    pub fn new_with_source(reader: EventReader<R>, config: DeserializerConfig, source: S) -> Self {
         Deserializer {
             depth: 0,
             reader: reader,
             peeked: None,
             is_map_value: false,
+            is_variant_value: false,
+            config,
+            source,
+            position: TextPosition::new(),
+            raw_peeked: VecDeque::new(),
+            trim_text: false,
+            after_end_element: false,
+            mixed_depths: Vec::new(),
+            elements_read: 0,
+            attribute_defaults: None,
         }
     }
 
-    pub fn new_from_reader(reader: R) -> Self {
-        let config = ParserConfig::new()
-            .trim_whitespace(true)
-            .whitespace_to_characters(true)
-            .cdata_to_characters(true)
-            .ignore_comments(true)
-            .coalesce_characters(true);
+    /// Turns this deserializer into an iterator over the children of the
+    /// root element, each deserialized as a `T`.
+    pub fn into_stream<T: de::Deserialize<'de>>(self) -> StreamDeserializer<'de, R, T, S> {
+        StreamDeserializer::new(self, vec!["*".to_string(), "*".to_string()])
+    }
 
-        Self::new(EventReader::new_with_config(reader, config))
+    /// Like `into_stream`, but skips children that aren't called `name`.
+    pub fn into_stream_named<T: de::Deserialize<'de>>(
+        self,
+        name: &str,
+    ) -> StreamDeserializer<'de, R, T, S> {
+        StreamDeserializer::new(self, vec!["*".to_string(), name.to_string()])
+    }
+
+    /// Turns this deserializer into an iterator over the elements matching
+    /// `path`, e.g. `/Envelope/Body/*`, each deserialized as a `T`. Segments
+    /// are matched against local names; `*` matches any name.
+    pub fn into_stream_at<T: de::Deserialize<'de>>(
+        self,
+        path: &str,
+    ) -> Result<StreamDeserializer<'de, R, T, S>> {
+        Ok(StreamDeserializer::new(self, stream::parse_path(path)?))
     }
 
     fn peek(&mut self) -> Result<&XmlEvent> {
@@ -98,14 +293,132 @@ impl<'de, R: Read> Deserializer<R> {
         })
     }
 
+    /// Reads the next event as the parser reports it, including comments,
+    /// CDATA sections and processing instructions.
+    fn raw_next(&mut self) -> Result<(XmlEvent, TextPosition)> {
+        if let Some(raw_peeked) = self.raw_peeked.pop_front() {
+            return Ok(raw_peeked);
+        }
+        loop {
+            let mut event = match self.reader.next() {
+                Ok(event) => event,
+                Err(error) => return Err(syntax_error(error, &self.config.limits)),
+            };
+            if self.config.internal_dtd {
+                self.apply_attribute_defaults(&mut event)?;
+            }
+            self.check_limits(&event)?;
+            match event {
+                XmlEvent::StartDocument { .. } => { /* skip */ },
+                other => return Ok((other, self.reader.position())),
+            }
+        }
+    }
+
+    /// Adds the attributes the internal DTD subset declares defaults for to
+    /// a start tag that leaves them out.
+    fn apply_attribute_defaults(&mut self, event: &mut XmlEvent) -> Result<()> {
+        if let XmlEvent::StartElement { ref name, ref mut attributes, ref namespace } = *event {
+            if self.attribute_defaults.is_none() {
+                let limits = &self.config.limits;
+                self.attribute_defaults = Some(AttributeDefaults::parse(
+                    self.reader.doctype().unwrap_or(""),
+                    &self.config.extra_entities(),
+                    limits.entity_expansion_depth.unwrap_or(dtd::DEFAULT_EXPANSION_DEPTH),
+                    limits.entity_expansion_length.unwrap_or(dtd::DEFAULT_EXPANSION_LENGTH),
+                )?);
+            }
+            if let Some(ref defaults) = self.attribute_defaults {
+                defaults.apply(name, attributes, namespace);
+            }
+        }
+        Ok(())
+    }
+
+    /// Enforces the configured limits on an event the parser just read.
+    ///
+    /// The parser itself stops at the attribute and text limits as it reads
+    /// (see `DeserializerConfig::parser_config`), and `LimitedReader` at the
+    /// document size, so these checks only make the errors exact.
+    fn check_limits(&mut self, event: &XmlEvent) -> Result<()> {
+        let limits = &self.config.limits;
+        match *event {
+            XmlEvent::StartElement { ref attributes, .. } => {
+                self.elements_read += 1;
+                if let Some(max) = limits.elements {
+                    if self.elements_read > max {
+                        return Err(ErrorKind::ElementLimitExceeded(max).into());
+                    }
+                }
+                if let Some(max) = limits.attributes {
+                    if attributes.len() > max {
+                        return Err(ErrorKind::AttributeLimitExceeded(max).into());
+                    }
+                }
+                Ok(())
+            },
+            XmlEvent::Characters(ref text) |
+            XmlEvent::CData(ref text) |
+            XmlEvent::Comment(ref text) |
+            XmlEvent::Whitespace(ref text) => limits.check_text_length(text),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the next event, with comments skipped and adjacent text and
+    /// CDATA sections merged into one `Characters` event.
+    ///
+    /// When trimming, text is trimmed at the start and end of its parent's
+    /// content, but keeps the whitespace separating it from a sibling
+    /// element, as in `Hello <b>world</b>`. Text that is all whitespace is
+    /// dropped, unless it separates two elements in mixed content, as in
+    /// `<b>hello</b> <b>world</b>`.
     fn inner_next(&mut self) -> Result<XmlEvent> {
         loop {
-            match self.reader.next().map_err(ErrorKind::Syntax)? {
-                XmlEvent::StartDocument { .. } |
-                XmlEvent::ProcessingInstruction { .. } |
-                XmlEvent::Comment(_) => { /* skip */ },
-                other => return Ok(other),
+            let (event, position) = self.raw_next()?;
+            self.position = position;
+            let mut text = match event {
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => text,
+                XmlEvent::Comment(_) | XmlEvent::ProcessingInstruction { .. } => continue,
+                other => {
+                    self.after_end_element = matches!(other, XmlEvent::EndElement { .. });
+                    return Ok(other);
+                },
+            };
+            let before_element = loop {
+                match self.raw_next()? {
+                    (XmlEvent::Characters(more), _) | (XmlEvent::CData(more), _) => {
+                        text.push_str(&more)
+                    },
+                    (XmlEvent::Comment(_), _) | (XmlEvent::ProcessingInstruction { .. }, _) => {},
+                    other => {
+                        let before_element = matches!(other.0, XmlEvent::StartElement { .. });
+                        self.raw_peeked.push_front(other);
+                        break before_element;
+                    },
+                }
+            };
+            self.config.limits.check_text_length(&text)?;
+            if self.trim_text {
+                let separator = self.after_end_element &&
+                    before_element &&
+                    self.mixed_depths.last() == Some(&self.depth);
+                if text.trim().is_empty() && !separator {
+                    continue;
+                }
+                let mut trimmed = text.as_str();
+                if !self.after_end_element {
+                    trimmed = trimmed.trim_start();
+                }
+                if !before_element {
+                    trimmed = trimmed.trim_end();
+                }
+                if trimmed.len() != text.len() {
+                    text = trimmed.to_string();
+                }
             }
+            self.after_end_element = false;
+            return Ok(XmlEvent::Characters(text));
         }
     }
 
@@ -118,8 +431,12 @@ impl<'de, R: Read> Deserializer<R> {
         match next {
             XmlEvent::StartElement { .. } => {
                 self.depth += 1;
+                self.config.limits.check_depth(self.depth)?;
             },
             XmlEvent::EndElement { .. } => {
+                if self.mixed_depths.last() == Some(&self.depth) {
+                    self.mixed_depths.pop();
+                }
                 self.depth -= 1;
             },
             _ => {},
@@ -128,14 +445,103 @@ impl<'de, R: Read> Deserializer<R> {
         Ok(next)
     }
 
+    /// Visits text that was just read, borrowing it from the input if the
+    /// source allows.
+    fn visit_text<V: de::Visitor<'de>>(&mut self, text: String, visitor: V) -> Result<V::Value> {
+        debug_assert!(self.peeked.is_none());
+        match self.source.borrow_text(self.position, &text) {
+            Some(borrowed) => visitor.visit_borrowed_str(borrowed),
+            None => visitor.visit_string(text),
+        }
+    }
+
+    /// Reads the next element with everything inside it as it is, keeping
+    /// whitespace, comments, CDATA sections and processing instructions.
+    fn read_element(&mut self) -> Result<Element> {
+        self.build_element().map(|(element, _, _)| element)
+    }
+
+    /// Reads the next element, along with the positions of its start and end
+    /// tags.
+    fn build_element(&mut self) -> Result<(Element, TextPosition, TextPosition)> {
+        self.unset_map_value();
+        let start = expect!(self.next()?, start @ XmlEvent::StartElement { .. } => Ok(start))?;
+        let start_position = self.position;
+        let mut builder = None;
+        element::build(&mut builder, start)?;
+        loop {
+            let (event, position) = self.raw_next()?;
+            if let Some(element) = element::build(&mut builder, event)? {
+                self.depth -= 1;
+                return Ok((element, start_position, position));
+            }
+            let nested = builder.as_ref().map_or(0, Vec::len);
+            self.config.limits.check_depth(self.depth + nested - 1)?;
+        }
+    }
+
+    /// Reads the content of the next element as markup, whitespace included.
+    ///
+    /// The content is taken from the input as it is written if the source
+    /// allows, and otherwise written out from the elements read. Either way,
+    /// child elements that use namespaces declared further up get those
+    /// declarations, which makes them written out too.
+    fn read_inner_xml(&mut self) -> Result<String> {
+        let (mut element, start, end) = self.build_element()?;
+        let scope = ::std::mem::take(&mut element.namespaces);
+        let mut declared = false;
+        for child in &mut element.children {
+            if let Node::Element(ref mut child) = *child {
+                declared |= child.declare_namespaces(&scope);
+            }
+        }
+        if !declared {
+            if let Some(raw) = self.source.raw_content(start, end) {
+                return Ok(raw.to_string());
+            }
+        }
+        Ok(Markup(&element.children).to_string())
+    }
+
+    /// Reads an element as a map, whose attributes are named after
+    /// `attribute_fields` when it is a struct variant.
+    fn read_map<V: de::Visitor<'de>>(
+        &mut self,
+        attribute_fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.unset_map_value();
+        expect!(self.next()?, XmlEvent::StartElement { name, attributes, .. } => {
+            let map = MapAccess::new(self, &name, attributes, &[]).with_attribute_fields(attribute_fields);
+            let map_value = visitor.visit_map(map)?;
+            self.expect_end_element(name)?;
+            Ok(map_value)
+        })
+    }
+
     fn set_map_value(&mut self) {
         self.is_map_value = true;
     }
 
     pub fn unset_map_value(&mut self) -> bool {
+        self.is_variant_value = false;
         ::std::mem::replace(&mut self.is_map_value, false)
     }
 
+    /// Marks the peeked element as a newtype variant, so that a sequence
+    /// is read from its children rather than from it and its siblings.
+    fn set_variant_value(&mut self) {
+        self.is_variant_value = self.is_map_value;
+    }
+
+    /// Keeps the whitespace between the children of the element being read,
+    /// which is mixed content.
+    fn read_mixed_content(&mut self) {
+        if self.mixed_depths.last() != Some(&self.depth) {
+            self.mixed_depths.push(self.depth);
+        }
+    }
+
     fn read_inner_value<V: de::Visitor<'de>, T, F: FnOnce(&mut Self) -> Result<T>>(
         &mut self,
         f: F,
@@ -165,6 +571,58 @@ impl<'de, R: Read> Deserializer<R> {
         })
     }
 
+    /// Moves the peeked event into `events`, along with everything inside it
+    /// if it starts an element, so it can be read again after `replay`.
+    fn set_aside(&mut self, events: &mut Vec<(XmlEvent, TextPosition)>) -> Result<()> {
+        self.peek()?;
+        let event = self.peeked.take().expect("peeked event");
+        let mut depth = match event {
+            XmlEvent::StartElement { .. } => 1,
+            _ => 0,
+        };
+        events.push((event, self.position));
+        while depth > 0 {
+            let (event, position) = self.raw_next()?;
+            match event {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                _ => {},
+            }
+            events.push((event, position));
+        }
+        Ok(())
+    }
+
+    /// Whether the peeked event starts an element holding only text, or
+    /// nothing at all.
+    fn peek_text_element(&mut self) -> Result<bool> {
+        match *self.peek()? {
+            XmlEvent::StartElement { .. } => {},
+            _ => return Ok(false),
+        }
+        let start = self.peeked.take();
+        let position = self.position;
+        let content = self.inner_next()?;
+        let is_text = matches!(content, XmlEvent::Characters(_) | XmlEvent::EndElement { .. });
+        self.raw_peeked.push_front((content, self.position));
+        self.position = position;
+        self.peeked = start;
+        Ok(is_text)
+    }
+
+    /// Reads the text of the element holding a whitespace-separated list.
+    fn read_list(&mut self) -> Result<String> {
+        if let XmlEvent::StartElement { .. } = *self.peek()? {
+            self.set_map_value()
+        }
+        self.read_inner_value::<de::IgnoredAny, String, _>(|this| {
+            if let XmlEvent::EndElement { .. } = *this.peek()? {
+                return Ok(String::new());
+            }
+            expect!(this.next()?, XmlEvent::Characters(s) => Ok(s))
+        })
+    }
+
     fn prepare_parse_type<V: de::Visitor<'de>>(&mut self) -> Result<String> {
         if let XmlEvent::StartElement { .. } = *self.peek()? {
             self.set_map_value()
@@ -186,17 +644,32 @@ impl<'de, R: Read> Deserializer<R> {
 macro_rules! deserialize_type {
     ($deserialize:ident => $visit:ident) => {
         fn $deserialize<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-            let value = self.prepare_parse_type::<V>()?.parse()?;
-            visitor.$visit(value)
+            let text = self.prepare_parse_type::<V>()?;
+            visitor.$visit(self.config.parse_number(&text)?)
         }
     }
 }
 
-impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
+impl<'de, R: Read, S: Source<'de>> de::Deserializer<'de> for &mut Deserializer<R, S> {
     type Error = Error;
 
-    forward_to_deserialize_any! {
-        newtype_struct identifier
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == ELEMENT_TOKEN {
+            let element = self.read_element()?;
+            visit_element(element, visitor)
+        } else if name == LIST_TOKEN {
+            let text = self.read_list()?;
+            visitor.visit_newtype_struct(ListDeserializer::new(text, &self.config))
+        } else if name == INNER_XML_TOKEN {
+            let markup = self.read_inner_xml()?;
+            visitor.visit_newtype_struct(markup.into_deserializer())
+        } else {
+            self.deserialize_any(visitor)
+        }
     }
 
     fn deserialize_struct<V: de::Visitor<'de>>(
@@ -207,11 +680,7 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
     ) -> Result<V::Value> {
         self.unset_map_value();
         expect!(self.next()?, XmlEvent::StartElement { name, attributes, .. } => {
-            let map_value = visitor.visit_map(MapAccess::new(
-                self,
-                attributes,
-                fields.contains(&"$value")
-            ))?;
+            let map_value = visitor.visit_map(MapAccess::new(self, &name, attributes, fields))?;
             self.expect_end_element(name)?;
             Ok(map_value)
         })
@@ -221,10 +690,12 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
     deserialize_type!(deserialize_i16 => visit_i16);
     deserialize_type!(deserialize_i32 => visit_i32);
     deserialize_type!(deserialize_i64 => visit_i64);
+    deserialize_type!(deserialize_i128 => visit_i128);
     deserialize_type!(deserialize_u8 => visit_u8);
     deserialize_type!(deserialize_u16 => visit_u16);
     deserialize_type!(deserialize_u32 => visit_u32);
     deserialize_type!(deserialize_u64 => visit_u64);
+    deserialize_type!(deserialize_u128 => visit_u128);
     deserialize_type!(deserialize_f32 => visit_f32);
     deserialize_type!(deserialize_f64 => visit_f64);
 
@@ -237,12 +708,7 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 return visitor.visit_bool(false);
             }
             expect!(this.next()?, XmlEvent::Characters(s) => {
-                match s.as_str() {
-                    "true" | "1" => visitor.visit_bool(true),
-                    "false" | "0" => visitor.visit_bool(false),
-                    _ => Err(de::Error::invalid_value(Unexpected::Str(&s), &"a boolean")),
-                }
-
+                visitor.visit_bool(this.config.parse_bool(&s)?)
             })
         })
     }
@@ -251,6 +717,10 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
         self.deserialize_string(visitor)
     }
 
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
     fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
         self.deserialize_string(visitor)
     }
@@ -290,16 +760,26 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        if self.config.xs_lists && self.is_map_value && self.peek_text_element()? {
+            let text = self.read_list()?;
+            return de::Deserializer::deserialize_tuple(
+                ListDeserializer::new(text, &self.config),
+                len,
+                visitor,
+            );
+        }
         visitor.visit_seq(SeqAccess::new(self, Some(len)))
     }
 
     fn deserialize_enum<V: de::Visitor<'de>>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value> {
-        self.read_inner_value::<V, V::Value, _>(|this| visitor.visit_enum(EnumAccess::new(this)))
+        self.read_inner_value::<V, V::Value, _>(|this| {
+            visitor.visit_enum(EnumAccess::new(this, variants))
+        })
     }
 
     fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
@@ -311,22 +791,26 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 return visitor.visit_str("");
             }
             expect!(this.next()?, XmlEvent::Characters(s) => {
-                visitor.visit_string(s)
+                this.visit_text(s, visitor)
             })
         })
     }
 
     fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.config.xs_lists && self.is_map_value && self.peek_text_element()? {
+            let text = self.read_list()?;
+            return de::Deserializer::deserialize_seq(ListDeserializer::new(text, &self.config), visitor);
+        }
+        if self.is_variant_value {
+            return self.read_inner_value::<V, V::Value, _>(|this| {
+                visitor.visit_seq(SeqAccess::new(this, None))
+            });
+        }
         visitor.visit_seq(SeqAccess::new(self, None))
     }
 
     fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        self.unset_map_value();
-        expect!(self.next()?, XmlEvent::StartElement { name, attributes, .. } => {
-            let map_value = visitor.visit_map(MapAccess::new(self, attributes, false))?;
-            self.expect_end_element(name)?;
-            Ok(map_value)
-        })
+        self.read_map(&[], visitor)
     }
 
     fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
@@ -349,10 +833,44 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        match *self.peek()? {
-            XmlEvent::StartElement { .. } => self.deserialize_map(visitor),
-            XmlEvent::EndElement { .. } => self.deserialize_unit(visitor),
-            _ => self.deserialize_string(visitor),
+        self.unset_map_value();
+        if let XmlEvent::EndElement { .. } = *self.peek()? {
+            return visitor.visit_unit();
         }
+        de::Deserializer::deserialize_any(self.read_any()?, visitor)
     }
 }
+
+/// Reports the parser giving up on an entity, or on one of the configured
+/// limits, under our own error kinds. The attribute defaults of the internal
+/// DTD subset fail with the same entity error.
+///
+/// xml-rs keeps its syntax error kinds private, so this goes by the messages
+/// of `SyntaxError::EntityTooBig` and `ExceededConfiguredLimit` as of xml-rs
+/// 0.8.29, the oldest version we build against. The `internal_dtd` and
+/// `security_limits` tests catch them changing. The parser has one error for
+/// all its limits, so it is only told apart when one of them is set.
+fn syntax_error(error: ::xml::reader::Error, limits: &Limits) -> Error {
+    match *error.kind() {
+        ::xml::reader::ErrorKind::Syntax(ref message) if message == "Entity too big" => {
+            return ErrorKind::EntityExpansionLimitExceeded.into();
+        },
+        ::xml::reader::ErrorKind::Syntax(ref message) if message == PARSER_LIMIT_MESSAGE => {
+            match (limits.attributes, limits.text_length) {
+                (Some(max), None) => return ErrorKind::AttributeLimitExceeded(max).into(),
+                (None, Some(max)) => return ErrorKind::TextLengthLimitExceeded(max).into(),
+                _ => {},
+            }
+        },
+        ::xml::reader::ErrorKind::Io(ref io_error) => {
+            if let Some(error) = limit::size_limit_error(io_error) {
+                return error;
+            }
+        },
+        _ => {},
+    }
+    ErrorKind::Syntax(error).into()
+}
+
+const PARSER_LIMIT_MESSAGE: &str =
+    "This document is larger/more complex than allowed by the parser's configuration";
@@ -0,0 +1,47 @@
+use float;
+
+/// Numbers `DeserializerConfig::parse_number` can read.
+pub trait Number: Sized {
+    /// Parses the decimal `text`, with an optional sign.
+    fn parse_decimal(text: &str) -> Option<Self>;
+
+    /// Parses the hexadecimal digits `text`, with an optional sign, or
+    /// returns `None` if numbers of this type aren't written in hex.
+    fn parse_hex(text: &str) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($integer:ident)*) => {
+        $(
+            impl Number for $integer {
+                fn parse_decimal(text: &str) -> Option<Self> {
+                    text.parse().ok()
+                }
+
+                fn parse_hex(text: &str) -> Option<Self> {
+                    $integer::from_str_radix(text, 16).ok()
+                }
+            }
+        )*
+    }
+}
+
+integer!(i8 i16 i32 i64 i128 u8 u16 u32 u64 u128);
+
+macro_rules! floating_point {
+    ($($float:ident)*) => {
+        $(
+            impl Number for $float {
+                fn parse_decimal(text: &str) -> Option<Self> {
+                    float::parse(text)
+                }
+
+                fn parse_hex(_text: &str) -> Option<Self> {
+                    None
+                }
+            }
+        )*
+    }
+}
+
+floating_point!(f32 f64);
@@ -1,19 +1,24 @@
 use std::io::Read;
 
 use serde::de;
+use xml::common::TextPosition;
 use xml::reader::XmlEvent;
 
 use de::Deserializer;
 use error::{Error, Result};
+use super::source::Source;
 
-pub struct SeqAccess<'a, R: 'a + Read> {
-    de: &'a mut Deserializer<R>,
+pub struct SeqAccess<'a, R: 'a + Read, S: 'a> {
+    de: &'a mut Deserializer<R, S>,
     max_size: Option<usize>,
     expected_name: Option<String>,
+    // Siblings read past while looking for more elements named
+    // `expected_name`, replayed once the sequence is done
+    set_aside: Vec<(XmlEvent, TextPosition)>,
 }
 
-impl<'a, R: 'a + Read> SeqAccess<'a, R> {
-    pub fn new(de: &'a mut Deserializer<R>, max_size: Option<usize>) -> Self {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> SeqAccess<'a, R, S> {
+    pub fn new(de: &'a mut Deserializer<R, S>, max_size: Option<usize>) -> Self {
         let expected_name = if de.unset_map_value() {
             debug_expect!(de.peek(), Ok(&XmlEvent::StartElement { ref name, .. }) => {
                 Some(name.local_name.clone())
@@ -25,11 +30,12 @@ impl<'a, R: 'a + Read> SeqAccess<'a, R> {
             de: de,
             max_size: max_size,
             expected_name: expected_name,
+            set_aside: Vec::new(),
         }
     }
 }
 
-impl<'de, 'a, R: 'a + Read> de::SeqAccess<'de> for SeqAccess<'a, R> {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> de::SeqAccess<'de> for SeqAccess<'a, R, S> {
     type Error = Error;
 
     fn next_element_seed<T: de::DeserializeSeed<'de>>(
@@ -45,14 +51,28 @@ impl<'de, 'a, R: 'a + Read> de::SeqAccess<'de> for SeqAccess<'a, R> {
             },
             None => {},
         }
-        let more = match (self.de.peek()?, self.expected_name.as_ref()) {
-            (&XmlEvent::StartElement { ref name, .. }, Some(expected_name)) => {
-                &name.local_name == expected_name
-            },
-            (&XmlEvent::EndElement { .. }, None) |
-            (_, Some(_)) |
-            (&XmlEvent::EndDocument { .. }, _) => false,
-            (_, None) => true,
+        let more = loop {
+            let interleaved = self.de.config.interleaved_sequences;
+            let more = match (self.de.peek()?, self.expected_name.as_ref()) {
+                (XmlEvent::StartElement { name, .. }, Some(expected_name)) => {
+                    if name.local_name == *expected_name {
+                        Some(true)
+                    } else if interleaved {
+                        None
+                    } else {
+                        Some(false)
+                    }
+                },
+                (XmlEvent::Characters(_), Some(_)) if interleaved => None,
+                (XmlEvent::EndElement { .. }, None) |
+                (_, Some(_)) |
+                (XmlEvent::EndDocument, _) => Some(false),
+                (_, None) => Some(true),
+            };
+            match more {
+                Some(more) => break more,
+                None => self.de.set_aside(&mut self.set_aside)?,
+            }
         };
         if more {
             if self.expected_name.is_some() {
@@ -68,3 +88,10 @@ impl<'de, 'a, R: 'a + Read> de::SeqAccess<'de> for SeqAccess<'a, R> {
         self.max_size
     }
 }
+
+impl<'a, R: 'a + Read, S: 'a> Drop for SeqAccess<'a, R, S> {
+    fn drop(&mut self) {
+        let set_aside = ::std::mem::take(&mut self.set_aside);
+        self.de.replay(set_aside);
+    }
+}
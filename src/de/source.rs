@@ -0,0 +1,270 @@
+use std::ops::Range;
+
+use xml::attribute::OwnedAttribute;
+use xml::common::TextPosition;
+
+/// The input a `Deserializer` can borrow strings from.
+///
+/// Text is only ever borrowed when it appears verbatim in the input, i.e.
+/// it contains no entity or character references, CDATA sections or
+/// normalized line breaks. Anything else is handed out as an owned `String`.
+pub trait Source<'de> {
+    /// Returns the input behind the text node that starts at `position`, if
+    /// the node is exactly `text`.
+    fn borrow_text(&mut self, position: TextPosition, text: &str) -> Option<&'de str>;
+
+    /// Returns where the value of each attribute of the start tag at
+    /// `position` is found in the input, if it is exactly the parsed value.
+    fn attribute_spans(
+        &mut self,
+        position: TextPosition,
+        attributes: &[OwnedAttribute],
+    ) -> Vec<Option<Range<usize>>>;
+
+    /// Returns a span previously found by `attribute_spans`.
+    fn slice(&self, span: Range<usize>) -> &'de str;
+
+    /// Returns the input between the start tag at `start` and the end tag at
+    /// `end`, which is the content of an element as it is written.
+    fn raw_content(&mut self, start: TextPosition, end: TextPosition) -> Option<&'de str> {
+        let _ = (start, end);
+        None
+    }
+}
+
+/// Any reader: nothing is borrowed.
+impl<'de> Source<'de> for () {
+    fn borrow_text(&mut self, _: TextPosition, _: &str) -> Option<&'de str> {
+        None
+    }
+
+    fn attribute_spans(
+        &mut self,
+        _: TextPosition,
+        attributes: &[OwnedAttribute],
+    ) -> Vec<Option<Range<usize>>> {
+        vec![None; attributes.len()]
+    }
+
+    fn slice(&self, _: Range<usize>) -> &'de str {
+        unreachable!("no attribute spans are ever handed out")
+    }
+}
+
+/// An in-memory document that strings can be borrowed from.
+pub struct SliceSource<'de> {
+    input: &'de str,
+    // Text position and byte offset of the last located event. Events come
+    // in document order, so locating the next one only walks forward.
+    row: u64,
+    column: u64,
+    offset: usize,
+}
+
+impl<'de> SliceSource<'de> {
+    pub fn new(input: &'de str) -> Self {
+        SliceSource {
+            input,
+            row: 0,
+            column: 0,
+            offset: 0,
+        }
+    }
+
+    fn locate(&mut self, position: TextPosition) -> Option<usize> {
+        if (position.row, position.column) < (self.row, self.column) {
+            self.row = 0;
+            self.column = 0;
+            self.offset = 0;
+        }
+        while self.row < position.row {
+            let line_end = self.input[self.offset..].find('\n')?;
+            self.offset += line_end + 1;
+            self.row += 1;
+            self.column = 0;
+        }
+        for c in self.input[self.offset..].chars() {
+            if self.column == position.column {
+                break;
+            }
+            if c == '\n' {
+                return None;
+            }
+            self.offset += c.len_utf8();
+            self.column += 1;
+        }
+        if self.column == position.column {
+            Some(self.offset)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'de> Source<'de> for SliceSource<'de> {
+    fn borrow_text(&mut self, position: TextPosition, text: &str) -> Option<&'de str> {
+        let offset = self.locate(position)?;
+        let mut rest = &self.input[offset..];
+        if !rest.starts_with(text) {
+            rest = rest.trim_start();
+        }
+        if rest.starts_with(text) {
+            Some(&rest[..text.len()])
+        } else {
+            None
+        }
+    }
+
+    fn attribute_spans(
+        &mut self,
+        position: TextPosition,
+        attributes: &[OwnedAttribute],
+    ) -> Vec<Option<Range<usize>>> {
+        // xml-rs may report the end of the start tag instead of its start,
+        // but `<` can't appear inside a tag, so the last one is where it starts
+        let raw = match self.locate(position).and_then(|offset| self.input[..=offset].rfind('<')) {
+            Some(offset) => raw_attributes(self.input, offset),
+            None => Vec::new(),
+        };
+        attributes
+            .iter()
+            .map(|attribute| {
+                let name = match attribute.name.prefix {
+                    Some(ref prefix) => format!("{}:{}", prefix, attribute.name.local_name),
+                    None => attribute.name.local_name.clone(),
+                };
+                raw.iter()
+                    .find(|(raw_name, _)| *raw_name == name)
+                    .map(|(_, span)| span.clone())
+                    .filter(|span| self.input[span.clone()] == *attribute.value)
+            })
+            .collect()
+    }
+
+    fn slice(&self, span: Range<usize>) -> &'de str {
+        &self.input[span]
+    }
+
+    fn raw_content(&mut self, start: TextPosition, end: TextPosition) -> Option<&'de str> {
+        // Like start tags, end tags are found by their last `<`
+        let start = self.locate(start).and_then(|offset| self.input[..=offset].rfind('<'))?;
+        let content_start = tag_end(self.input, start)? + 1;
+        if self.input[..content_start].ends_with("/>") {
+            return Some("");
+        }
+        let end = self.locate(end).and_then(|offset| self.input[..=offset].rfind('<'))?;
+        if end < content_start || !self.input[end..].starts_with("</") {
+            return None;
+        }
+        Some(&self.input[content_start..end])
+    }
+}
+
+/// Returns where the `>` ending the tag at `offset` is.
+fn tag_end(input: &str, offset: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, b) in input.bytes().enumerate().skip(offset) {
+        match (quote, b) {
+            (None, b'"') | (None, b'\'') => quote = Some(b),
+            (Some(open), b) if open == b => quote = None,
+            (None, b'>') => return Some(i),
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Splits the start tag at `offset` into attribute names and value spans.
+fn raw_attributes(input: &str, offset: usize) -> Vec<(&str, Range<usize>)> {
+    let bytes = input.as_bytes();
+    let is_space = |b: u8| b == b' ' || b == b'\t' || b == b'\r' || b == b'\n';
+    let is_name_end = |b: u8| is_space(b) || b == b'=' || b == b'>' || b == b'/';
+    let mut attributes = Vec::new();
+    if bytes.get(offset) != Some(&b'<') {
+        return attributes;
+    }
+
+    let mut i = offset + 1;
+    while i < bytes.len() && !is_name_end(bytes[i]) {
+        i += 1;
+    }
+    loop {
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+        let name_start = i;
+        while i < bytes.len() && !is_name_end(bytes[i]) {
+            i += 1;
+        }
+        if i == name_start {
+            return attributes;
+        }
+        let name = &input[name_start..i];
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+        if bytes.get(i) != Some(&b'=') {
+            return attributes;
+        }
+        i += 1;
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+        let quote = match bytes.get(i) {
+            Some(&quote) if quote == b'"' || quote == b'\'' => quote,
+            _ => return attributes,
+        };
+        let value_start = i + 1;
+        let value_end = match bytes[value_start..].iter().position(|&b| b == quote) {
+            Some(length) => value_start + length,
+            None => return attributes,
+        };
+        attributes.push((name, value_start..value_end));
+        i = value_end + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xml::name::OwnedName;
+
+    #[test]
+    fn test_raw_attributes() {
+        let input = r#"<a><b  y='2' z = "&amp;"/></a>"#;
+        let attributes = raw_attributes(input, 3)
+            .into_iter()
+            .map(|(name, span)| (name, &input[span]))
+            .collect::<Vec<_>>();
+        assert_eq!(attributes, vec![("y", "2"), ("z", "&amp;")]);
+    }
+
+    #[test]
+    fn test_attribute_spans() {
+        let input = "<a>\n  <b y='2' z=\"&amp;\"/></a>";
+        let attribute = |name: &str, value: &str| OwnedAttribute {
+            name: OwnedName::local(name),
+            value: value.to_string(),
+        };
+        let mut source = SliceSource::new(input);
+        let position = TextPosition { row: 1, column: 2 };
+        let spans = source.attribute_spans(
+            position,
+            &[attribute("z", "&"), attribute("y", "2")],
+        );
+        assert_eq!(spans[0], None);
+        assert_eq!(spans[1].clone().map(|span| source.slice(span)), Some("2"));
+    }
+
+    #[test]
+    fn test_raw_content() {
+        let input = "<a>\n  <b y='>'>x<c/>&amp;</b><d/></a>";
+        let mut source = SliceSource::new(input);
+        let b = TextPosition { row: 1, column: 2 };
+        let end_b = TextPosition { row: 1, column: 22 };
+        assert_eq!(source.raw_content(b, end_b), Some("x<c/>&amp;"));
+        let d = TextPosition { row: 1, column: 26 };
+        assert_eq!(source.raw_content(d, d), Some(""));
+        assert_eq!(source.raw_content(b, b), None);
+    }
+}
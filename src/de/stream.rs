@@ -0,0 +1,187 @@
+use std::io::Read;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, IgnoredAny};
+use xml::common::TextPosition;
+use xml::reader::XmlEvent;
+
+use de::Deserializer;
+use error::{Error, ErrorKind, Result};
+use super::source::Source;
+
+/// An iterator that deserializes the children of the root element one at a
+/// time, so that documents with many repeated items can be processed without
+/// holding all of them in memory.
+///
+/// An item that fails to deserialize is reported as an error and skipped, and
+/// the iterator goes on with the next one. Errors reading the document itself,
+/// such as malformed markup or an exceeded limit, end the iteration.
+///
+/// Created by `Deserializer::into_stream`, `Deserializer::into_stream_named`
+/// and `Deserializer::into_stream_at`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::Deserializer;
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Entry {
+///     id: u32,
+/// }
+/// # fn main() {
+/// let s = r##"<feed><title>News</title><entry id="1"/><entry id="2"/></feed>"##;
+/// let entries = Deserializer::new_from_reader(s.as_bytes())
+///     .into_stream_named::<Entry>("entry")
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(entries, vec![Entry { id: 1 }, Entry { id: 2 }]);
+/// # }
+/// ```
+pub struct StreamDeserializer<'de, R: Read, T, S = ()> {
+    de: Deserializer<R, S>,
+    path: Vec<String>,
+    // Names of the elements on the way to the current match
+    open: Vec<String>,
+    // The depth and position of the start tag of the item being read
+    item: Option<(usize, TextPosition)>,
+    finished: bool,
+    marker: PhantomData<fn(&'de ()) -> T>,
+}
+
+impl<'de, R: Read, T: de::Deserialize<'de>, S: Source<'de>> StreamDeserializer<'de, R, T, S> {
+    /// Creates an iterator over the elements read by `de` that match `path`,
+    /// a list of element names (or `*` for any name) starting at the root.
+    pub fn new(de: Deserializer<R, S>, path: Vec<String>) -> Self {
+        StreamDeserializer {
+            de,
+            path,
+            open: Vec::new(),
+            item: None,
+            finished: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Skips ahead to the start of the next element matching the path.
+    fn seek(&mut self) -> Result<bool> {
+        loop {
+            let descend = match *self.de.peek()? {
+                XmlEvent::StartElement { ref name, .. } => {
+                    let segment = &self.path[self.open.len()];
+                    if segment != "*" && *segment != name.local_name {
+                        None
+                    } else if self.open.len() + 1 == self.path.len() {
+                        return Ok(true);
+                    } else {
+                        Some(name.local_name.clone())
+                    }
+                },
+                XmlEvent::EndElement { .. } => {
+                    self.de.next()?;
+                    self.open.pop();
+                    continue;
+                },
+                XmlEvent::EndDocument => return Ok(false),
+                _ => {
+                    self.de.next()?;
+                    continue;
+                },
+            };
+            match descend {
+                Some(name) => {
+                    self.de.next()?;
+                    self.open.push(name);
+                },
+                None => {
+                    IgnoredAny::deserialize(&mut self.de)?;
+                },
+            }
+        }
+    }
+
+    fn next_item(&mut self) -> Result<Option<T>> {
+        if self.seek()? {
+            self.item = Some((self.de.depth, self.de.position));
+            T::deserialize(&mut self.de).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Skips what is left of an item that failed to deserialize.
+    fn skip_item(&mut self) -> Result<()> {
+        let (depth, start) = match self.item.take() {
+            Some(item) => item,
+            None => return Ok(()),
+        };
+        self.de.unset_map_value();
+        if self.de.depth == depth {
+            // Either the item failed before its start tag was read, or after
+            // its end tag was
+            if self.de.position == start {
+                if let XmlEvent::StartElement { .. } = *self.de.peek()? {
+                    IgnoredAny::deserialize(&mut self.de)?;
+                }
+            }
+            return Ok(());
+        }
+        while self.de.depth > depth {
+            self.de.next()?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `error` comes from reading the document rather than from the item
+/// being deserialized, so that there is no next item to go on with.
+fn ends_stream(error: &Error) -> bool {
+    matches!(
+        *error.kind(),
+        ErrorKind::Io(_) |
+            ErrorKind::Syntax(_) |
+            ErrorKind::DepthLimitExceeded(_) |
+            ErrorKind::AttributeLimitExceeded(_) |
+            ErrorKind::TextLengthLimitExceeded(_) |
+            ErrorKind::DocumentSizeLimitExceeded(_) |
+            ErrorKind::ElementLimitExceeded(_) |
+            ErrorKind::EntityExpansionLimitExceeded
+    )
+}
+
+/// Splits a path like `/Envelope/Body/*` into its segments.
+pub fn parse_path(path: &str) -> Result<Vec<String>> {
+    let segments = match path.strip_prefix('/') {
+        Some(path) => path.split('/').map(str::to_string).collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    if segments.is_empty() || segments.iter().any(String::is_empty) {
+        return Err(ErrorKind::InvalidPath(path.to_string()).into());
+    }
+    Ok(segments)
+}
+
+impl<'de, R: Read, T: de::Deserialize<'de>, S: Source<'de>> Iterator
+    for StreamDeserializer<'de, R, T, S> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.finished {
+            return None;
+        }
+        match self.next_item() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            },
+            Err(error) => {
+                if ends_stream(&error) || self.skip_item().is_err() {
+                    self.finished = true;
+                }
+                Some(Err(error))
+            },
+        }
+    }
+}
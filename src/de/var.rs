@@ -6,25 +6,40 @@ use xml::reader::XmlEvent;
 
 use de::Deserializer;
 use error::{Error, Result};
+use super::source::Source;
 
-pub struct EnumAccess<'a, R: 'a + Read> {
-    de: &'a mut Deserializer<R>,
+pub struct EnumAccess<'a, R: 'a + Read, S: 'a> {
+    de: &'a mut Deserializer<R, S>,
+    variants: &'static [&'static str],
 }
 
-impl<'a, R: 'a + Read> EnumAccess<'a, R> {
-    pub fn new(de: &'a mut Deserializer<R>) -> Self {
-        EnumAccess { de: de }
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> EnumAccess<'a, R, S> {
+    pub fn new(de: &'a mut Deserializer<R, S>, variants: &'static [&'static str]) -> Self {
+        EnumAccess { de, variants }
     }
 }
 
-impl<'de, 'a, R: 'a + Read> de::EnumAccess<'de> for EnumAccess<'a, R> {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> de::EnumAccess<'de> for EnumAccess<'a, R, S> {
     type Error = Error;
-    type Variant = VariantAccess<'a, R>;
+    type Variant = VariantAccess<'a, R, S>;
 
     fn variant_seed<V: de::DeserializeSeed<'de>>(
         self,
         seed: V,
-    ) -> Result<(V::Value, VariantAccess<'a, R>)> {
+    ) -> Result<(V::Value, VariantAccess<'a, R, S>)> {
+        // In mixed content, text nodes belong to the `$value` variant when
+        // there is one, rather than naming a unit variant.
+        if self.variants.contains(&"$value") {
+            self.de.read_mixed_content();
+        }
+        if let XmlEvent::Characters(_) = *self.de.peek()? {
+            if self.variants.contains(&"$value") {
+                let name: V::Value = seed.deserialize(
+                    IntoDeserializer::<Error>::into_deserializer("$value"),
+                )?;
+                return Ok((name, VariantAccess::new(self.de)));
+            }
+        }
         let name = expect!(
             self.de.peek()?,
 
@@ -38,17 +53,17 @@ impl<'de, 'a, R: 'a + Read> de::EnumAccess<'de> for EnumAccess<'a, R> {
     }
 }
 
-pub struct VariantAccess<'a, R: 'a + Read> {
-    de: &'a mut Deserializer<R>,
+pub struct VariantAccess<'a, R: 'a + Read, S: 'a> {
+    de: &'a mut Deserializer<R, S>,
 }
 
-impl<'a, R: 'a + Read> VariantAccess<'a, R> {
-    pub fn new(de: &'a mut Deserializer<R>) -> Self {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> VariantAccess<'a, R, S> {
+    pub fn new(de: &'a mut Deserializer<R, S>) -> Self {
         VariantAccess { de: de }
     }
 }
 
-impl<'de, 'a, R: 'a + Read> de::VariantAccess<'de> for VariantAccess<'a, R> {
+impl<'de, 'a, R: 'a + Read, S: 'a + Source<'de>> de::VariantAccess<'de> for VariantAccess<'a, R, S> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -67,6 +82,7 @@ impl<'de, 'a, R: 'a + Read> de::VariantAccess<'de> for VariantAccess<'a, R> {
     }
 
     fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        self.de.set_variant_value();
         seed.deserialize(&mut *self.de)
     }
 
@@ -76,9 +92,9 @@ impl<'de, 'a, R: 'a + Read> de::VariantAccess<'de> for VariantAccess<'a, R> {
 
     fn struct_variant<V: de::Visitor<'de>>(
         self,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value> {
-        self.de.deserialize_map(visitor)
+        self.de.read_map(fields, visitor)
     }
 }
@@ -0,0 +1,744 @@
+//! A generic tree for documents of unknown or dynamic structure.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::Read;
+
+use serde::de::{self, Deserialize};
+use serde::ser::{Serialize, SerializeStruct, SerializeStructVariant, Serializer};
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+use xml::namespace::{Namespace, NS_XMLNS_PREFIX, NS_XML_PREFIX};
+use xml::reader::{EventReader, XmlEvent};
+
+pub use xml::attribute::OwnedAttribute;
+pub use xml::name::OwnedName;
+
+use cdata::CDataSections;
+use de::{Deserializer, DeserializerConfig};
+use error::{ErrorKind, Result};
+
+mod part;
+mod ser;
+
+use self::part::{Part, PartSerializer};
+
+pub(crate) use self::ser::{
+    attribute_field, collect_start_tag, map_key, no_element, not_an_element, other_element, root_name, Field,
+    StartTag,
+};
+
+/// Deserializes a `T` from an element, following the same conventions as
+/// deserializing it from the element's markup, but without going through
+/// text.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::{from_element, from_str, to_element, Element};
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Price {
+///     currency: String,
+///     #[serde(rename = "$value")]
+///     amount: f64,
+/// }
+/// # fn main() {
+/// let quote: Element = from_str(r##"<quote><price currency="EUR">1.5</price></quote>"##).unwrap();
+/// let price: Price = from_element(quote.get_child("price").unwrap().clone()).unwrap();
+/// assert_eq!(price, Price { currency: "EUR".to_string(), amount: 1.5 });
+///
+/// let element = to_element(&price).unwrap();
+/// assert_eq!(element.name.local_name, "Price");
+/// # }
+/// ```
+pub fn from_element<'de, T: Deserialize<'de>>(element: Element) -> Result<T> {
+    T::deserialize(&mut Deserializer::new_from_element(element))
+}
+
+/// Serializes `value` into an element, following the same conventions as
+/// `to_string`, but without going through text.
+pub fn to_element<T: Serialize>(value: &T) -> Result<Element> {
+    let mut nodes = value.serialize(ser::NodeSerializer::new_root(root_name::<T>()))?.into_iter();
+    match (nodes.next(), nodes.next()) {
+        (Some(Node::Element(element)), None) => Ok(element),
+        _ => Err(ErrorKind::Custom("the value isn't serialized as one element".into()).into()),
+    }
+}
+
+/// The newtype struct name `Element` is (de)serialized under, which tells
+/// this crate's `Deserializer` and `Serializer` to hand over the element as
+/// is.
+pub(crate) const ELEMENT_TOKEN: &str = "$serde_xml_rs::Element";
+
+/// An XML element with everything inside it.
+///
+/// `Element` can be deserialized from any element and serialized back,
+/// whether it is the whole document or a field of a struct:
+///
+/// ```rust
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::{from_str, to_string, Element};
+/// # fn main() {
+/// let s = r##"<item xmlns:a="urn:a" a:id="1"><!-- c --><a:name>x &amp; y</a:name><![CDATA[<z>]]></item>"##;
+/// let item: Element = from_str(s).unwrap();
+/// assert_eq!(item.get_child("name").unwrap().text(), "x & y");
+/// assert_eq!(item.attribute("id"), Some("1"));
+/// assert_eq!(to_string(&item).unwrap(), s);
+/// # }
+/// ```
+///
+/// Unlike typed fields, an `Element` keeps text as it is, whitespace
+/// included, and keeps processing instructions, so that it is written back
+/// the way it was read. The XML declaration and DOCTYPE belong to the
+/// document rather than to its root element and aren't part of the tree.
+///
+/// Other formats see an `Element` as a struct of its `name`, `namespaces`,
+/// `attributes` and `children`, and can also read one from a string of
+/// markup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Element {
+    pub name: OwnedName,
+    /// Namespaces declared on this element, by prefix (empty for the default
+    /// namespace). The root of a tree declares all namespaces it uses.
+    pub namespaces: BTreeMap<String, String>,
+    pub attributes: Vec<OwnedAttribute>,
+    pub children: Vec<Node>,
+}
+
+/// A child of an `Element`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    CData(String),
+    /// A comment, which can't contain `--` or end in `-`. The serializers
+    /// and `to_element` reject such comments, but `Display` writes them as
+    /// they are.
+    Comment(String),
+    ProcessingInstruction { name: String, data: Option<String> },
+}
+
+impl Element {
+    /// Creates an element without namespace, attributes or children.
+    pub fn new(name: &str) -> Self {
+        Element {
+            name: OwnedName::local(name),
+            namespaces: BTreeMap::new(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Parses the root element of a document.
+    pub fn parse<R: Read>(reader: R) -> Result<Self> {
+        let config = DeserializerConfig::new().parser_config();
+        let mut reader = EventReader::new_with_config(reader, config);
+        let mut builder = None;
+        loop {
+            let event = reader.next().map_err(ErrorKind::Syntax)?;
+            if let Some(element) = build(&mut builder, event)? {
+                return Ok(element);
+            }
+        }
+    }
+
+    /// Returns the value of the attribute with the local name `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.local_name == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Returns the child elements.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match *child {
+            Node::Element(ref element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /// Returns the first child element with the local name `name`.
+    pub fn get_child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|element| element.name.local_name == name)
+    }
+
+    /// Returns the text and CDATA sections directly inside this element.
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match *child {
+                Node::Text(ref text) | Node::CData(ref text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn from_start(
+        name: OwnedName,
+        attributes: Vec<OwnedAttribute>,
+        namespace: &Namespace,
+        parent: Option<&Namespace>,
+    ) -> Self {
+        let namespaces = namespace
+            .0
+            .iter()
+            .filter(|&(prefix, uri)| match parent {
+                Some(parent) => parent.get(prefix) != Some(uri.as_str()),
+                None => {
+                    prefix != NS_XML_PREFIX && prefix != NS_XMLNS_PREFIX &&
+                        !(prefix.is_empty() && uri.is_empty())
+                },
+            })
+            .map(|(prefix, uri)| (prefix.clone(), uri.clone()))
+            .collect();
+        Element {
+            name,
+            namespaces,
+            attributes,
+            children: Vec::new(),
+        }
+    }
+
+    /// Declares the namespaces of `scope` that names in this element or
+    /// below use without declaring, so that it reads the same on its own as
+    /// in the document it came from. Returns whether any were missing.
+    pub(crate) fn declare_namespaces(&mut self, scope: &BTreeMap<String, String>) -> bool {
+        let mut used = BTreeSet::new();
+        self.undeclared_prefixes(&[], &mut used);
+        let missing: Vec<(String, String)> = used
+            .into_iter()
+            .filter_map(|prefix| scope.get(prefix).map(|uri| (prefix.to_string(), uri.clone())))
+            .collect();
+        let declared = !missing.is_empty();
+        self.namespaces.extend(missing);
+        declared
+    }
+
+    fn undeclared_prefixes<'a>(&'a self, outer: &[&'a str], used: &mut BTreeSet<&'a str>) {
+        let mut declared = outer.to_vec();
+        declared.extend(self.namespaces.keys().map(String::as_str));
+        let element_prefix = self.name.prefix_ref().unwrap_or("");
+        let attribute_prefixes = self.attributes.iter().filter_map(|a| a.name.prefix_ref());
+        for prefix in Some(element_prefix).into_iter().chain(attribute_prefixes) {
+            if !declared.contains(&prefix) {
+                used.insert(prefix);
+            }
+        }
+        for child in self.elements() {
+            child.undeclared_prefixes(&declared, used);
+        }
+    }
+
+    /// Returns the events a parser would report for this element.
+    pub(crate) fn into_events(self) -> Vec<XmlEvent> {
+        let mut events = Vec::new();
+        self.push_events(&Namespace::empty(), &mut events);
+        events
+    }
+
+    fn push_events(self, parent: &Namespace, events: &mut Vec<XmlEvent>) {
+        let mut namespace = parent.clone();
+        for (prefix, uri) in self.namespaces {
+            namespace.put(prefix, uri);
+        }
+        events.push(XmlEvent::StartElement {
+            name: self.name.clone(),
+            attributes: self.attributes,
+            namespace: namespace.clone(),
+        });
+        for child in self.children {
+            match child {
+                Node::Element(element) => element.push_events(&namespace, events),
+                Node::Text(text) => events.push(XmlEvent::Characters(text)),
+                Node::CData(text) => events.push(XmlEvent::CData(text)),
+                Node::Comment(text) => events.push(XmlEvent::Comment(text)),
+                Node::ProcessingInstruction { name, data } => {
+                    events.push(XmlEvent::ProcessingInstruction { name, data })
+                },
+            }
+        }
+        events.push(XmlEvent::EndElement { name: self.name });
+    }
+
+    fn write(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.name.borrow().to_repr();
+        write!(f, "<{}{}>{}</{}>", name, Attributes(self), Markup(&self.children), name)
+    }
+}
+
+/// Writes the namespace declarations and attributes of an element, each
+/// preceded by a space, the way they appear in its start tag.
+pub(crate) struct Attributes<'a>(pub &'a Element);
+
+impl<'a> fmt::Display for Attributes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (prefix, uri) in &self.0.namespaces {
+            if prefix.is_empty() {
+                write!(f, " xmlns=\"{}\"", escape_str_attribute(uri))?;
+            } else {
+                write!(f, " xmlns:{}=\"{}\"", prefix, escape_str_attribute(uri))?;
+            }
+        }
+        for attribute in &self.0.attributes {
+            write!(
+                f,
+                " {}=\"{}\"",
+                attribute.name.borrow().to_repr(),
+                escape_str_attribute(&attribute.value)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes nodes as markup, the way they appear inside an element.
+pub(crate) struct Markup<'a>(pub &'a [Node]);
+
+impl<'a> fmt::Display for Markup<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.0 {
+            match *node {
+                Node::Element(ref element) => element.write(f)?,
+                Node::Text(ref text) => write!(f, "{}", escape_str_pcdata(text))?,
+                Node::CData(ref text) => write!(f, "{}", CDataSections(text))?,
+                Node::Comment(ref text) => write!(f, "<!--{}-->", text)?,
+                Node::ProcessingInstruction { ref name, data: Some(ref data) } => {
+                    write!(f, "<?{} {}?>", name, data)?
+                },
+                Node::ProcessingInstruction { ref name, data: None } => write!(f, "<?{}?>", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the element as markup.
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+/// Adds `event` to the tree being built from a stream of parser events,
+/// returning the tree once its root element has ended.
+pub(crate) fn build(
+    builder: &mut Option<Vec<(Element, Namespace)>>,
+    event: XmlEvent,
+) -> Result<Option<Element>> {
+    let stack = match *builder {
+        Some(ref mut stack) => stack,
+        None => {
+            return match event {
+                XmlEvent::StartElement { name, attributes, namespace } => {
+                    let element = Element::from_start(name, attributes, &namespace, None);
+                    *builder = Some(vec![(element, namespace)]);
+                    Ok(None)
+                },
+                XmlEvent::EndElement { .. } | XmlEvent::EndDocument => Err(
+                    ErrorKind::UnexpectedToken("StartElement".into(), format!("{:?}", event))
+                        .into(),
+                ),
+                _ => Ok(None),
+            }
+        },
+    };
+    let child = match event {
+        XmlEvent::StartElement { name, attributes, namespace } => {
+            let element = {
+                let parent = &stack[stack.len() - 1].1;
+                Element::from_start(name, attributes, &namespace, Some(parent))
+            };
+            stack.push((element, namespace));
+            return Ok(None);
+        },
+        XmlEvent::EndElement { .. } => {
+            let (element, _) = stack.pop().expect("unbalanced tree");
+            if stack.is_empty() {
+                return Ok(Some(element));
+            }
+            Node::Element(element)
+        },
+        XmlEvent::Characters(text) | XmlEvent::Whitespace(text) => {
+            if text.is_empty() {
+                return Ok(None);
+            }
+            Node::Text(text)
+        },
+        XmlEvent::CData(text) => Node::CData(text),
+        XmlEvent::Comment(text) => Node::Comment(text),
+        XmlEvent::EndDocument => {
+            return Err(ErrorKind::UnexpectedToken("EndElement".into(), "EndDocument".into()).into());
+        },
+        XmlEvent::ProcessingInstruction { name, data } => Node::ProcessingInstruction { name, data },
+        XmlEvent::StartDocument { .. } => return Ok(None),
+    };
+    let last = stack.len() - 1;
+    stack[last].0.children.push(child);
+    Ok(None)
+}
+
+const ELEMENT_FIELDS: &[&str] = &["name", "namespaces", "attributes", "children"];
+const NAME_FIELDS: &[&str] = &["local_name", "namespace", "prefix"];
+const ATTRIBUTE_FIELDS: &[&str] = &["name", "value"];
+const INSTRUCTION_FIELDS: &[&str] = &["name", "data"];
+const NODE_VARIANTS: &[&str] = &["Element", "Text", "CData", "Comment", "ProcessingInstruction"];
+
+/// Takes `element` apart the way its `Serialize` impl describes it.
+fn element_part(element: Element) -> Part {
+    let namespaces = element
+        .namespaces
+        .into_iter()
+        .map(|(prefix, uri)| (prefix, Part::Str(uri)))
+        .collect();
+    let attributes = element
+        .attributes
+        .into_iter()
+        .map(|attribute| {
+            Part::Map(vec![
+                ("name".to_string(), name_part(attribute.name)),
+                ("value".to_string(), Part::Str(attribute.value)),
+            ])
+        })
+        .collect();
+    let children = element.children.into_iter().map(node_part).collect();
+    Part::Map(vec![
+        ("name".to_string(), name_part(element.name)),
+        ("namespaces".to_string(), Part::Map(namespaces)),
+        ("attributes".to_string(), Part::Seq(attributes)),
+        ("children".to_string(), Part::Seq(children)),
+    ])
+}
+
+fn name_part(name: OwnedName) -> Part {
+    Part::Map(vec![
+        ("local_name".to_string(), Part::Str(name.local_name)),
+        ("namespace".to_string(), optional_part(name.namespace)),
+        ("prefix".to_string(), optional_part(name.prefix)),
+    ])
+}
+
+fn node_part(node: Node) -> Part {
+    let (variant, part) = match node {
+        Node::Element(element) => ("Element", element_part(element)),
+        Node::Text(text) => ("Text", Part::Str(text)),
+        Node::CData(text) => ("CData", Part::Str(text)),
+        Node::Comment(text) => ("Comment", Part::Str(text)),
+        Node::ProcessingInstruction { name, data } => {
+            let fields = vec![
+                ("name".to_string(), Part::Str(name)),
+                ("data".to_string(), optional_part(data)),
+            ];
+            ("ProcessingInstruction", Part::Map(fields))
+        },
+    };
+    Part::Variant(variant.to_string(), Box::new(part))
+}
+
+fn optional_part(text: Option<String>) -> Part {
+    text.map_or(Part::Unit, Part::Str)
+}
+
+/// Parses markup as the content of an element.
+pub(crate) fn parse_fragment(markup: &str) -> Result<Vec<Node>> {
+    let reader = (&b"<fragment>"[..]).chain(markup.as_bytes()).chain(&b"</fragment>"[..]);
+    Ok(Element::parse(reader)?.children)
+}
+
+/// Hands `element` to the visitor of `Element::deserialize`.
+pub(crate) fn visit_element<'de, V: de::Visitor<'de>>(
+    element: Element,
+    visitor: V,
+) -> Result<V::Value> {
+    visitor.visit_newtype_struct(element_part(element))
+}
+
+/// Builds back the element that the value `Element` serializes under
+/// `ELEMENT_TOKEN` describes.
+pub(crate) fn rebuild_element<T: ?Sized + Serialize>(value: &T) -> Result<Element> {
+    let element = de::Deserializer::deserialize_any(value.serialize(PartSerializer)?, ElementVisitor)?;
+    check_comments(&element.children)?;
+    Ok(element)
+}
+
+/// Rejects comments that can't be written as markup, there being no way to
+/// escape `--` in them.
+fn check_comments(nodes: &[Node]) -> Result<()> {
+    for node in nodes {
+        match *node {
+            Node::Element(ref element) => check_comments(&element.children)?,
+            Node::Comment(ref text) if text.contains("--") || text.ends_with('-') => {
+                return Err(ErrorKind::InvalidComment(text.clone()).into());
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Serializes as a struct of the element's name, namespaces, attributes and
+/// children, which is how other formats see it. This crate's serializers
+/// build the element back from that and write it as markup.
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(ELEMENT_TOKEN, &ElementFields(self))
+    }
+}
+
+struct ElementFields<'a>(&'a Element);
+
+impl<'a> Serialize for ElementFields<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        let element = self.0;
+        let mut fields = serializer.serialize_struct("Element", ELEMENT_FIELDS.len())?;
+        fields.serialize_field("name", &NameFields(&element.name))?;
+        fields.serialize_field("namespaces", &element.namespaces)?;
+        fields.serialize_field("attributes", &AttributeList(&element.attributes))?;
+        fields.serialize_field("children", &NodeList(&element.children))?;
+        fields.end()
+    }
+}
+
+struct AttributeList<'a>(&'a [OwnedAttribute]);
+
+impl<'a> Serialize for AttributeList<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(AttributeFields))
+    }
+}
+
+struct NodeList<'a>(&'a [Node]);
+
+impl<'a> Serialize for NodeList<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(NodeFields))
+    }
+}
+
+struct NameFields<'a>(&'a OwnedName);
+
+impl<'a> Serialize for NameFields<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        let mut fields = serializer.serialize_struct("Name", NAME_FIELDS.len())?;
+        fields.serialize_field("local_name", &self.0.local_name)?;
+        fields.serialize_field("namespace", &self.0.namespace)?;
+        fields.serialize_field("prefix", &self.0.prefix)?;
+        fields.end()
+    }
+}
+
+struct AttributeFields<'a>(&'a OwnedAttribute);
+
+impl<'a> Serialize for AttributeFields<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        let mut fields = serializer.serialize_struct("Attribute", ATTRIBUTE_FIELDS.len())?;
+        fields.serialize_field("name", &NameFields(&self.0.name))?;
+        fields.serialize_field("value", &self.0.value)?;
+        fields.end()
+    }
+}
+
+struct NodeFields<'a>(&'a Node);
+
+impl<'a> Serialize for NodeFields<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        match *self.0 {
+            Node::Element(ref element) => {
+                serializer.serialize_newtype_variant("Node", 0, "Element", element)
+            },
+            Node::Text(ref text) => serializer.serialize_newtype_variant("Node", 1, "Text", text),
+            Node::CData(ref text) => serializer.serialize_newtype_variant("Node", 2, "CData", text),
+            Node::Comment(ref text) => {
+                serializer.serialize_newtype_variant("Node", 3, "Comment", text)
+            },
+            Node::ProcessingInstruction { ref name, ref data } => {
+                let mut fields = serializer.serialize_struct_variant(
+                    "Node",
+                    4,
+                    "ProcessingInstruction",
+                    INSTRUCTION_FIELDS.len(),
+                )?;
+                fields.serialize_field("name", name)?;
+                fields.serialize_field("data", data)?;
+                fields.end()
+            },
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(ELEMENT_TOKEN, ElementVisitor)
+    }
+}
+
+struct ElementVisitor;
+
+impl<'de> de::Visitor<'de> for ElementVisitor {
+    type Value = Element;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an XML element")
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> ::std::result::Result<Element, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    /// Other formats may also hold an element as markup.
+    fn visit_str<E: de::Error>(self, markup: &str) -> ::std::result::Result<Element, E> {
+        Element::parse(markup.as_bytes()).map_err(E::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<Element, A::Error> {
+        let mut name = None;
+        let mut element = Element::new("");
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = Some(map.next_value::<Name>()?.0),
+                "namespaces" => element.namespaces = map.next_value()?,
+                "attributes" => {
+                    let attributes = map.next_value::<Vec<Attribute>>()?;
+                    element.attributes = attributes.into_iter().map(|attribute| attribute.0).collect();
+                },
+                "children" => {
+                    let children = map.next_value::<Vec<Child>>()?;
+                    element.children = children.into_iter().map(|child| child.0).collect();
+                },
+                other => return Err(de::Error::unknown_field(other, ELEMENT_FIELDS)),
+            }
+        }
+        element.name = name.ok_or_else(|| de::Error::missing_field("name"))?;
+        Ok(element)
+    }
+}
+
+struct Name(OwnedName);
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_struct("Name", NAME_FIELDS, NameVisitor)
+    }
+}
+
+struct NameVisitor;
+
+impl<'de> de::Visitor<'de> for NameVisitor {
+    type Value = Name;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an XML name")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<Name, A::Error> {
+        let mut name = OwnedName::local(String::new());
+        let mut local_name = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "local_name" => local_name = Some(map.next_value()?),
+                "namespace" => name.namespace = map.next_value()?,
+                "prefix" => name.prefix = map.next_value()?,
+                other => return Err(de::Error::unknown_field(other, NAME_FIELDS)),
+            }
+        }
+        name.local_name = local_name.ok_or_else(|| de::Error::missing_field("local_name"))?;
+        Ok(Name(name))
+    }
+}
+
+struct Attribute(OwnedAttribute);
+
+impl<'de> Deserialize<'de> for Attribute {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_struct("Attribute", ATTRIBUTE_FIELDS, AttributeVisitor)
+    }
+}
+
+struct AttributeVisitor;
+
+impl<'de> de::Visitor<'de> for AttributeVisitor {
+    type Value = Attribute;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an XML attribute")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<Attribute, A::Error> {
+        let mut name = None;
+        let mut value = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = Some(map.next_value::<Name>()?.0),
+                "value" => value = Some(map.next_value()?),
+                other => return Err(de::Error::unknown_field(other, ATTRIBUTE_FIELDS)),
+            }
+        }
+        Ok(Attribute(OwnedAttribute {
+            name: name.ok_or_else(|| de::Error::missing_field("name"))?,
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+        }))
+    }
+}
+
+struct Child(Node);
+
+impl<'de> Deserialize<'de> for Child {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_enum("Node", NODE_VARIANTS, ChildVisitor)
+    }
+}
+
+struct ChildVisitor;
+
+impl<'de> de::Visitor<'de> for ChildVisitor {
+    type Value = Child;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an XML node")
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> ::std::result::Result<Child, A::Error> {
+        use serde::de::VariantAccess;
+
+        let (variant, node) = data.variant::<String>()?;
+        let node = match variant.as_str() {
+            "Element" => Node::Element(node.newtype_variant()?),
+            "Text" => Node::Text(node.newtype_variant()?),
+            "CData" => Node::CData(node.newtype_variant()?),
+            "Comment" => Node::Comment(node.newtype_variant()?),
+            "ProcessingInstruction" => node.struct_variant(INSTRUCTION_FIELDS, InstructionVisitor)?,
+            other => return Err(de::Error::unknown_variant(other, NODE_VARIANTS)),
+        };
+        Ok(Child(node))
+    }
+}
+
+struct InstructionVisitor;
+
+impl<'de> de::Visitor<'de> for InstructionVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a processing instruction")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> ::std::result::Result<Node, A::Error> {
+        let mut name = None;
+        let mut data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = Some(map.next_value()?),
+                "data" => data = map.next_value()?,
+                other => return Err(de::Error::unknown_field(other, INSTRUCTION_FIELDS)),
+            }
+        }
+        Ok(Node::ProcessingInstruction {
+            name: name.ok_or_else(|| de::Error::missing_field("name"))?,
+            data,
+        })
+    }
+}
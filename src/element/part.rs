@@ -0,0 +1,387 @@
+use serde::de::{self, IntoDeserializer};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::ser::{self, Impossible, Serialize};
+
+use error::{Error, ErrorKind, Result};
+
+/// An `Element` taken apart into strings, sequences and maps, the form its
+/// `Serialize` and `Deserialize` impls describe it in.
+///
+/// This crate's serializers collect that description into a `Part` to build
+/// the element back, and its deserializer hands a `Part` of the element it
+/// read to the visitor, so that an element goes through as is without being
+/// written as markup and parsed again.
+pub(crate) enum Part {
+    Unit,
+    Str(String),
+    Seq(Vec<Part>),
+    Map(Vec<(String, Part)>),
+    Variant(String, Box<Part>),
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Part {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Part {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Part::Unit => visitor.visit_unit(),
+            Part::Str(text) => visitor.visit_string(text),
+            Part::Seq(parts) => {
+                let mut seq = SeqDeserializer::new(parts.into_iter());
+                let result = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(result)
+            },
+            Part::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let result = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(result)
+            },
+            Part::Variant(variant, part) => visitor.visit_enum(VariantPart(variant, *part)),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Part::Unit => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+struct VariantPart(String, Part);
+
+impl<'de> de::EnumAccess<'de> for VariantPart {
+    type Error = Error;
+    type Variant = Part;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Part)> {
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.0))?;
+        Ok((variant, self.1))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Part {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+/// A `Serializer` collecting the `Part` a value describes itself as.
+pub(crate) struct PartSerializer;
+
+fn unsupported<T>(operation: &str) -> Result<T> {
+    Err(ErrorKind::UnsupportedOperation(operation.to_string()).into())
+}
+
+#[allow(unused_variables)]
+impl ser::Serializer for PartSerializer {
+    type Ok = Part;
+    type Error = Error;
+
+    type SerializeSeq = Parts;
+    type SerializeTuple = Parts;
+    type SerializeTupleStruct = Impossible<Part, Error>;
+    type SerializeTupleVariant = Impossible<Part, Error>;
+    type SerializeMap = Parts;
+    type SerializeStruct = Parts;
+    type SerializeStructVariant = Parts;
+
+    fn serialize_bool(self, v: bool) -> Result<Part> {
+        unsupported("serialize_bool")
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Part> {
+        unsupported("serialize_i8")
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Part> {
+        unsupported("serialize_i16")
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Part> {
+        unsupported("serialize_i32")
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Part> {
+        unsupported("serialize_i64")
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Part> {
+        unsupported("serialize_u8")
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Part> {
+        unsupported("serialize_u16")
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Part> {
+        unsupported("serialize_u32")
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Part> {
+        unsupported("serialize_u64")
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Part> {
+        unsupported("serialize_f32")
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Part> {
+        unsupported("serialize_f64")
+    }
+
+    fn serialize_char(self, v: char) -> Result<Part> {
+        Ok(Part::Str(v.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Part> {
+        Ok(Part::Str(value.to_string()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Part> {
+        unsupported("serialize_bytes")
+    }
+
+    fn serialize_none(self) -> Result<Part> {
+        Ok(Part::Unit)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Part> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Part> {
+        Ok(Part::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Part> {
+        Ok(Part::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Part> {
+        Ok(Part::Variant(variant.to_string(), Box::new(Part::Unit)))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Part> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Part> {
+        Ok(Part::Variant(variant.to_string(), Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Parts> {
+        Ok(Parts::default())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Parts> {
+        Ok(Parts::default())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        unsupported("serialize_tuple_struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        unsupported("serialize_tuple_variant")
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Parts> {
+        Ok(Parts::default())
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Parts> {
+        Ok(Parts::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Parts> {
+        Ok(Parts {
+            variant: Some(variant.to_string()),
+            ..Parts::default()
+        })
+    }
+}
+
+/// The items of a sequence or the entries of a map or struct, as they are
+/// serialized.
+#[derive(Default)]
+pub(crate) struct Parts {
+    variant: Option<String>,
+    items: Vec<Part>,
+    entries: Vec<(String, Part)>,
+    next_key: Option<String>,
+}
+
+impl Parts {
+    fn add_entry<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> Result<()> {
+        let value = value.serialize(PartSerializer)?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn into_map(self) -> Part {
+        let map = Part::Map(self.entries);
+        match self.variant {
+            Some(variant) => Part::Variant(variant, Box::new(map)),
+            None => map,
+        }
+    }
+}
+
+impl ser::SerializeSeq for Parts {
+    type Ok = Part;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(PartSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Part> {
+        Ok(Part::Seq(self.items))
+    }
+}
+
+impl ser::SerializeTuple for Parts {
+    type Ok = Part;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Part> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeMap for Parts {
+    type Ok = Part;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(PartSerializer)? {
+            Part::Str(key) => {
+                self.next_key = Some(key);
+                Ok(())
+            },
+            _ => Err(ErrorKind::Custom("map keys must be strings".into()).into()),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.next_key.take().unwrap_or_default();
+        self.add_entry(key, value)
+    }
+
+    fn end(self) -> Result<Part> {
+        Ok(self.into_map())
+    }
+}
+
+impl ser::SerializeStruct for Parts {
+    type Ok = Part;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.add_entry(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Part> {
+        Ok(self.into_map())
+    }
+}
+
+impl ser::SerializeStructVariant for Parts {
+    type Ok = Part;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.add_entry(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Part> {
+        Ok(self.into_map())
+    }
+}
@@ -0,0 +1,1071 @@
+use std::fmt::Display;
+
+use serde::ser::{self, Impossible, Serialize};
+use xml::common::{is_name_char, is_name_start_char};
+
+use cdata::CDATA_TOKEN;
+use element::{parse_fragment, rebuild_element, Element, Node, OwnedAttribute, OwnedName, ELEMENT_TOKEN};
+use error::{Error, ErrorKind, Result};
+use float::XsdFloat;
+use inner_xml::INNER_XML_TOKEN;
+use list::LIST_TOKEN;
+use ser::AttrValueSerializer;
+
+/// A `Serializer` building the nodes `ser::Serializer` would write.
+///
+/// Like `ser::Serializer`, it wraps the value in a `tag` element when the
+/// struct field, map entry or sequence it belongs to names one.
+pub struct NodeSerializer {
+    tag: Option<String>,
+    /// The name a map is written as at the root, where nothing else names it.
+    root: Option<String>,
+}
+
+impl NodeSerializer {
+    pub fn new(tag: Option<String>) -> Self {
+        NodeSerializer { tag, root: None }
+    }
+
+    /// Creates a serializer for the root of a tree, which a map is written
+    /// as an element `root` of.
+    pub(crate) fn new_root(root: String) -> Self {
+        NodeSerializer {
+            tag: None,
+            root: Some(root),
+        }
+    }
+
+    fn wrap(self, children: Vec<Node>) -> Vec<Node> {
+        match self.tag {
+            Some(tag) => vec![element(tag, children)],
+            None => children,
+        }
+    }
+
+    fn primitive<P: Display>(self, primitive: P) -> Result<Vec<Node>> {
+        Ok(self.wrap(vec![Node::Text(primitive.to_string())]))
+    }
+}
+
+/// Returns the name the root element of a `T` gets when `T` is serialized
+/// as a map, as structs with flattened fields are: the name of the type,
+/// since a map doesn't tell the name of the struct.
+pub(crate) fn root_name<T: ?Sized>() -> String {
+    let name = ::std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    name.trim_start_matches('&').to_string()
+}
+
+/// Serializes a map key into the name of a field.
+pub(crate) fn map_key<T: ?Sized + Serialize>(key: &T) -> Result<String> {
+    match key.serialize(AttrValueSerializer)? {
+        Some(key) => Ok(key),
+        None => Err(ErrorKind::Custom("map keys must be primitives".into()).into()),
+    }
+}
+
+/// The error for a map entry that adds to the start tag, in a map that
+/// isn't written as an element of its own.
+pub(crate) fn no_element<T>(key: &str) -> Result<T> {
+    Err(ErrorKind::Custom(format!("{} has no element to go to", key)).into())
+}
+
+/// The prefix that makes a struct field an attribute.
+const ATTRIBUTE_PREFIX: &str = "@";
+
+/// Returns the name of the field that takes the attribute `name`, which is
+/// how both the serializers and the deserializer tell attribute fields.
+pub(crate) fn attribute_field(name: &str) -> String {
+    format!("{}{}", ATTRIBUTE_PREFIX, name)
+}
+
+/// The part of a struct's element a field goes to, which its name tells.
+pub(crate) enum Field<'a> {
+    /// An `@name` field, written as an attribute.
+    Attribute(&'a str),
+    /// A `$attrs` map, whose entries are written as attributes.
+    Attributes,
+    /// A `$other` element, whose attributes and children are merged in.
+    Other,
+    /// Content: the child element a field is named after, or `$value`,
+    /// which is written inline.
+    Content(Option<&'a str>),
+}
+
+impl<'a> Field<'a> {
+    pub fn new(key: &'a str) -> Self {
+        if let Some(name) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+            return Field::Attribute(name);
+        }
+        match key {
+            "$attrs" => Field::Attributes,
+            "$other" => Field::Other,
+            "$value" => Field::Content(None),
+            _ => Field::Content(Some(key)),
+        }
+    }
+}
+
+/// The start tag of the element a struct is written as, with the namespaces
+/// and attributes its fields add to it.
+///
+/// `ser::Serializer` and `NodeSerializer` both collect a struct's start tag
+/// with it, so that they agree on how fields become attributes.
+pub(crate) struct StartTag {
+    element: Element,
+}
+
+impl StartTag {
+    pub fn new(name: &str) -> Self {
+        StartTag { element: Element::new(name) }
+    }
+
+    /// Adds the attribute of an `@name` field, unless the value is `None`.
+    pub fn add_field<T: ?Sized + Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        if let Some(value) = value.serialize(AttrValueSerializer)? {
+            self.add_attribute(OwnedName::local(name), value);
+        }
+        Ok(())
+    }
+
+    /// Adds the entries of a `$attrs` map, where `xmlns` and `xmlns:prefix`
+    /// keys declare namespaces. Keys are checked to be names, as they are
+    /// written into the start tag as they are.
+    pub fn add_map<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        for (name, value) in attribute_map(value)? {
+            if !is_qualified_name(&name) {
+                return Err(ErrorKind::InvalidAttributeName(name).into());
+            }
+            let name: OwnedName = name.parse().unwrap_or_else(|()| OwnedName::local(name));
+            let declared = match (name.prefix_ref(), name.local_name.as_str()) {
+                (Some("xmlns"), prefix) => Some(prefix.to_string()),
+                (None, "xmlns") => Some(String::new()),
+                _ => None,
+            };
+            match declared {
+                Some(prefix) => {
+                    self.element.namespaces.insert(prefix, value);
+                },
+                None => self.add_attribute(name, value),
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the namespaces and attributes of a `$other` element, returning
+    /// its children.
+    pub fn add_other(&mut self, other: Element) -> Vec<Node> {
+        for (prefix, uri) in other.namespaces {
+            self.element.namespaces.entry(prefix).or_insert(uri);
+        }
+        for attribute in other.attributes {
+            self.add_attribute(attribute.name, attribute.value);
+        }
+        other.children
+    }
+
+    /// Adds an attribute, unless one of the same name was added before, as
+    /// `$attrs` and `$other` may repeat those of other fields.
+    fn add_attribute(&mut self, name: OwnedName, value: String) {
+        if !self.element.attributes.iter().any(|attribute| attribute.name == name) {
+            self.element.attributes.push(OwnedAttribute::new(name, value));
+        }
+    }
+
+    /// Returns the element, still without children.
+    pub fn finish(mut self) -> Element {
+        // Prefixes from `$attrs` are only bound by its own declarations
+        let namespaces = &self.element.namespaces;
+        for attribute in &mut self.element.attributes {
+            if let (Some(prefix), None) = (&attribute.name.prefix, &attribute.name.namespace) {
+                attribute.name.namespace = namespaces.get(prefix).cloned();
+            }
+        }
+        self.element
+    }
+}
+
+/// Collects the start tag of the struct `value` is written as, if it is one,
+/// without serializing the content of the struct.
+///
+/// `ser::Serializer` writes a struct's start tag from it before any of the
+/// content, so that attribute fields may come anywhere without it having to
+/// hold the content back.
+pub(crate) fn collect_start_tag<T: ?Sized + Serialize>(value: &T) -> Result<Option<StartTag>> {
+    value.serialize(StartTagSerializer)
+}
+
+/// A `Serializer` looking through `Option`s and newtype variants for a
+/// struct, and at nothing but the fields of that struct that add to the
+/// start tag.
+struct StartTagSerializer;
+
+#[allow(unused_variables)]
+impl ser::Serializer for StartTagSerializer {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    type SerializeSeq = Skip;
+    type SerializeTuple = Skip;
+    type SerializeTupleStruct = Skip;
+    type SerializeTupleVariant = Skip;
+    type SerializeMap = StartTagFields;
+    type SerializeStruct = StartTagFields;
+    type SerializeStructVariant = StartTagFields;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(Skip)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        Ok(Skip)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Ok(Skip)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(Skip)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(StartTagFields::new(""))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        Ok(StartTagFields::new(name))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(StartTagFields::new(variant))
+    }
+}
+
+/// Passes over a value without a start tag of its own.
+struct Skip;
+
+impl ser::SerializeSeq for Skip {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+}
+
+impl ser::SerializeTuple for Skip {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+}
+
+impl ser::SerializeTupleStruct for Skip {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+}
+
+impl ser::SerializeTupleVariant for Skip {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+}
+
+/// Adds the fields or map entries that go to the start tag, skipping the
+/// content.
+struct StartTagFields {
+    start: StartTag,
+    next_key: Option<String>,
+}
+
+impl StartTagFields {
+    fn new(name: &str) -> Self {
+        StartTagFields {
+            start: StartTag::new(name),
+            next_key: None,
+        }
+    }
+
+    fn add_entry<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        match Field::new(key) {
+            Field::Attribute(name) => self.start.add_field(name, value),
+            Field::Attributes => self.start.add_map(value),
+            Field::Other => {
+                self.start.add_other(other_element(value)?);
+                Ok(())
+            },
+            Field::Content(_) => Ok(()),
+        }
+    }
+}
+
+impl ser::SerializeMap for StartTagFields {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(map_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.next_key.take().unwrap_or_default();
+        self.add_entry(&key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.start))
+    }
+}
+
+impl ser::SerializeStruct for StartTagFields {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.add_entry(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(self.start))
+    }
+}
+
+impl ser::SerializeStructVariant for StartTagFields {
+    type Ok = Option<StartTag>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+/// Serializes the value of a `$other` field, which has to be an `Element`.
+pub(crate) fn other_element<T: ?Sized + Serialize>(value: &T) -> Result<Element> {
+    value.serialize(OtherSerializer)
+}
+
+/// The error for a `$other` field that isn't an `Element`, which would lose
+/// the names of what it holds.
+pub(crate) fn not_an_element<T>() -> Result<T> {
+    Err(ErrorKind::Custom("$other must be an Element".to_string()).into())
+}
+
+/// A `Serializer` accepting nothing but an `Element`.
+struct OtherSerializer;
+
+#[allow(unused_variables)]
+impl ser::Serializer for OtherSerializer {
+    type Ok = Element;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Element, Error>;
+    type SerializeTuple = Impossible<Element, Error>;
+    type SerializeTupleStruct = Impossible<Element, Error>;
+    type SerializeTupleVariant = Impossible<Element, Error>;
+    type SerializeMap = Impossible<Element, Error>;
+    type SerializeStruct = Impossible<Element, Error>;
+    type SerializeStructVariant = Impossible<Element, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_char(self, v: char) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_none(self) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Element> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Element> {
+        if name == ELEMENT_TOKEN {
+            rebuild_element(value)
+        } else {
+            not_an_element()
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Element> {
+        not_an_element()
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        not_an_element()
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        not_an_element()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        not_an_element()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        not_an_element()
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        not_an_element()
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        not_an_element()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        not_an_element()
+    }
+}
+
+/// Whether `name` is an XML name with at most one colon, which separates a
+/// prefix from the local name.
+fn is_qualified_name(name: &str) -> bool {
+    fn is_part(part: &str) -> bool {
+        let mut chars = part.chars();
+        chars.next().is_some_and(|c| c != ':' && is_name_start_char(c)) &&
+            chars.all(|c| c != ':' && is_name_char(c))
+    }
+    match name.find(':') {
+        Some(colon) => is_part(&name[..colon]) && is_part(&name[colon + 1..]),
+        None => is_part(name),
+    }
+}
+
+/// Serializes the map of a `$attrs` field into attribute names and values.
+fn attribute_map<T: ?Sized + Serialize>(value: &T) -> Result<Vec<(String, String)>> {
+    let entries = value.serialize(NodeSerializer::new(None))?;
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Node::Element(ref entry) if entry.children.iter().all(|child| matches!(*child, Node::Text(_))) => {
+                Ok((entry.name.local_name.clone(), entry.text()))
+            },
+            _ => Err(ErrorKind::Custom("$attrs must be a map of primitives".to_string()).into()),
+        })
+        .collect()
+}
+
+fn element(name: String, children: Vec<Node>) -> Node {
+    let mut element = Element::new(&name);
+    element.children = children;
+    Node::Element(element)
+}
+
+fn unsupported<T>(operation: &str) -> Result<T> {
+    Err(ErrorKind::UnsupportedOperation(operation.to_string()).into())
+}
+
+#[allow(unused_variables)]
+impl ser::Serializer for NodeSerializer {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    type SerializeSeq = Seq;
+    type SerializeTuple = Seq;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Map;
+    type SerializeStruct = Struct;
+    type SerializeStructVariant = Struct;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        self.primitive(XsdFloat(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        self.primitive(XsdFloat(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.primitive(v)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok> {
+        self.primitive(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
+        unsupported("serialize_bytes")
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(self.wrap(Vec::new()))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok> {
+        match self.tag {
+            Some(tag) => Ok(vec![element(tag, Vec::new())]),
+            None => Ok(vec![element(name.to_string(), Vec::new())]),
+        }
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        match self.tag {
+            Some(tag) => Ok(vec![element(tag, vec![Node::Text(variant.to_string())])]),
+            None => Ok(vec![element(variant.to_string(), Vec::new())]),
+        }
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        if name == ELEMENT_TOKEN {
+            // The element has a name of its own
+            Ok(vec![Node::Element(rebuild_element(value)?)])
+        } else if name == LIST_TOKEN {
+            match value.serialize(AttrValueSerializer)? {
+                Some(text) => self.primitive(text),
+                None => self.serialize_none(),
+            }
+        } else if name == INNER_XML_TOKEN {
+            match value.serialize(AttrValueSerializer)? {
+                Some(markup) => Ok(self.wrap(parse_fragment(&markup)?)),
+                None => self.serialize_none(),
+            }
+        } else if name == CDATA_TOKEN {
+            match value.serialize(AttrValueSerializer)? {
+                Some(text) => Ok(self.wrap(vec![Node::CData(text)])),
+                None => self.serialize_none(),
+            }
+        } else {
+            unsupported("serialize_newtype_struct")
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        if variant == "$value" {
+            value.serialize(self)
+        } else {
+            let inner = value.serialize(NodeSerializer::new(None))?;
+            Ok(self.wrap(vec![element(variant.to_string(), inner)]))
+        }
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(Seq {
+            tag: self.tag,
+            nodes: Vec::new(),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        unsupported("serialize_tuple_struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        unsupported("serialize_tuple_variant")
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        let element = self.tag.or(self.root).map(|tag| Struct {
+            start: StartTag::new(&tag),
+            outer: None,
+            nodes: Vec::new(),
+        });
+        Ok(Map {
+            element,
+            nodes: Vec::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        let name = self.tag.unwrap_or_else(|| name.to_string());
+        Ok(Struct {
+            start: StartTag::new(&name),
+            outer: None,
+            nodes: Vec::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(Struct {
+            start: StartTag::new(variant),
+            outer: self.tag,
+            nodes: Vec::new(),
+        })
+    }
+}
+
+/// Repeats the element the sequence was assigned to for every value.
+pub struct Seq {
+    tag: Option<String>,
+    nodes: Vec<Node>,
+}
+
+impl ser::SerializeSeq for Seq {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let nodes = value.serialize(NodeSerializer::new(self.tag.clone()))?;
+        self.nodes.extend(nodes);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.nodes)
+    }
+}
+
+impl ser::SerializeTuple for Seq {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Turns every entry into an element named after its key, the same way as
+/// the fields of a struct, in the element the map was assigned to if any.
+pub struct Map {
+    element: Option<Struct>,
+    nodes: Vec<Node>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for Map {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(map_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.next_key.take().unwrap_or_default();
+        if let Some(ref mut element) = self.element {
+            return element.add_entry(&key, value);
+        }
+        match Field::new(&key) {
+            Field::Content(tag) => {
+                let nodes = value.serialize(NodeSerializer::new(tag.map(str::to_string)))?;
+                self.nodes.extend(nodes);
+                Ok(())
+            },
+            _ => no_element(&key),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        match self.element {
+            Some(element) => ser::SerializeStruct::end(element),
+            None => Ok(self.nodes),
+        }
+    }
+}
+
+/// Turns every field into a child element, except for `$value`, which is
+/// written inline, and `@name`, `$attrs` and `$other` fields, which add to
+/// the start tag.
+pub struct Struct {
+    start: StartTag,
+    outer: Option<String>,
+    nodes: Vec<Node>,
+}
+
+impl Struct {
+    fn add_entry<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        match Field::new(key) {
+            Field::Attribute(name) => self.start.add_field(name, value),
+            Field::Attributes => self.start.add_map(value),
+            Field::Other => {
+                let children = self.start.add_other(other_element(value)?);
+                self.nodes.extend(children);
+                Ok(())
+            },
+            Field::Content(tag) => {
+                let nodes = value.serialize(NodeSerializer::new(tag.map(str::to_string)))?;
+                self.nodes.extend(nodes);
+                Ok(())
+            },
+        }
+    }
+}
+
+impl ser::SerializeStruct for Struct {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.add_entry(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        let mut element = self.start.finish();
+        element.children = self.nodes;
+        Ok(NodeSerializer::new(self.outer).wrap(vec![Node::Element(element)]))
+    }
+}
+
+impl ser::SerializeStructVariant for Struct {
+    type Ok = Vec<Node>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeStruct::end(self)
+    }
+}
@@ -32,6 +32,50 @@ error_chain! {
             description("unsupported operation")
             display("unsupported operation: '{}'", operation)
         }
+        InvalidNumber(number_type: String, text: String) {
+            description("invalid number")
+            display("invalid {}: '{}'", number_type, text)
+        }
+        DepthLimitExceeded(limit: usize) {
+            description("document nested too deeply")
+            display("document nested deeper than {} elements", limit)
+        }
+        AttributeLimitExceeded(limit: usize) {
+            description("too many attributes")
+            display("element has more than {} attributes", limit)
+        }
+        TextLengthLimitExceeded(limit: usize) {
+            description("text too long")
+            display("text longer than {} bytes", limit)
+        }
+        DocumentSizeLimitExceeded(limit: usize) {
+            description("document too large")
+            display("document larger than {} bytes", limit)
+        }
+        ElementLimitExceeded(limit: usize) {
+            description("too many elements")
+            display("document has more than {} elements", limit)
+        }
+        EntityExpansionLimitExceeded {
+            description("entity expansion too large")
+            display("entities expand deeper or longer than allowed")
+        }
+        InvalidPath(path: String) {
+            description("invalid path")
+            display("invalid path: '{}'", path)
+        }
+        PathNotFound(path: String) {
+            description("no element matches the path")
+            display("no element matches the path '{}'", path)
+        }
+        InvalidAttributeName(name: String) {
+            description("invalid attribute name")
+            display("invalid attribute name: '{}'", name)
+        }
+        InvalidComment(text: String) {
+            description("invalid comment")
+            display("comment contains '--' or ends in '-': '{}'", text)
+        }
     }
 }
 
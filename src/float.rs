@@ -0,0 +1,114 @@
+//! Floating point numbers in the lexical space of `xs:float` and `xs:double`.
+
+use std::fmt::{self, Display, LowerExp};
+use std::str::FromStr;
+
+/// Parses `text` as XML Schema writes floating point numbers: decimals with
+/// an optional exponent, `INF`, `-INF` and `NaN`.
+///
+/// Spellings only Rust accepts, such as `inf` or `infinity`, are rejected.
+pub fn parse<F: FromStr>(text: &str) -> Option<F> {
+    let rust = match text {
+        "INF" | "+INF" => "inf",
+        "-INF" => "-inf",
+        "NaN" => "NaN",
+        _ if is_decimal(text) => text,
+        _ => return None,
+    };
+    rust.parse().ok()
+}
+
+/// Matches `(\+|-)?([0-9]+(\.[0-9]*)?|\.[0-9]+)([Ee](\+|-)?[0-9]+)?`.
+fn is_decimal(text: &str) -> bool {
+    fn sign(bytes: &[u8]) -> usize {
+        match bytes.first() {
+            Some(&b'+') | Some(&b'-') => 1,
+            _ => 0,
+        }
+    }
+
+    fn digits(bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    let bytes = text.as_bytes();
+    let mut i = sign(bytes);
+    let integer = digits(&bytes[i..]);
+    i += integer;
+    let mut fraction = 0;
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        fraction = digits(&bytes[i..]);
+        i += fraction;
+    }
+    if integer + fraction == 0 {
+        return false;
+    }
+    if let Some(&b'e') | Some(&b'E') = bytes.get(i) {
+        i += 1;
+        i += sign(&bytes[i..]);
+        let exponent = digits(&bytes[i..]);
+        if exponent == 0 {
+            return false;
+        }
+        i += exponent;
+    }
+    i == bytes.len()
+}
+
+/// Displays a floating point number the way XML Schema writes it, with `INF`,
+/// `-INF` and `NaN` for the special values. Very large and very small
+/// magnitudes are written with an exponent, like `1e300`.
+pub struct XsdFloat<F>(pub F);
+
+impl<F: Display + LowerExp + Copy + Into<f64>> Display for XsdFloat<F> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let value: f64 = self.0.into();
+        if value.is_nan() {
+            formatter.write_str("NaN")
+        } else if value == f64::INFINITY {
+            formatter.write_str("INF")
+        } else if value == f64::NEG_INFINITY {
+            formatter.write_str("-INF")
+        } else if value != 0.0 && !(1e-7..1e16).contains(&value.abs()) {
+            LowerExp::fmt(&self.0, formatter)
+        } else {
+            Display::fmt(&self.0, formatter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse::<f64>("1.5").unwrap(), 1.5);
+        assert_eq!(parse::<f64>("-.5e+2").unwrap(), -50.0);
+        assert_eq!(parse::<f64>("3.").unwrap(), 3.0);
+        assert_eq!(parse::<f32>("12E-1").unwrap(), 1.2);
+        assert_eq!(parse::<f64>("INF").unwrap(), f64::INFINITY);
+        assert_eq!(parse::<f64>("-INF").unwrap(), f64::NEG_INFINITY);
+        assert!(parse::<f64>("NaN").unwrap().is_nan());
+
+        for text in &["inf", "infinity", "nan", "-NaN", ".", "1e", "e5", "1.5.2", " 1", ""] {
+            assert!(parse::<f64>(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(XsdFloat(1.5f64).to_string(), "1.5");
+        assert_eq!(XsdFloat(-0.25f32).to_string(), "-0.25");
+        assert_eq!(XsdFloat(f64::INFINITY).to_string(), "INF");
+        assert_eq!(XsdFloat(f32::NEG_INFINITY).to_string(), "-INF");
+        assert_eq!(XsdFloat(f64::NAN).to_string(), "NaN");
+        assert_eq!(XsdFloat(0.0f64).to_string(), "0");
+        assert_eq!(XsdFloat(123456789.0f64).to_string(), "123456789");
+        assert_eq!(XsdFloat(1e300f64).to_string(), "1e300");
+        assert_eq!(XsdFloat(-2.5e-10f64).to_string(), "-2.5e-10");
+        assert_eq!(XsdFloat(f32::MAX).to_string(), "3.4028235e38");
+        assert_eq!(parse::<f64>(&XsdFloat(f64::MIN_POSITIVE).to_string()), Some(f64::MIN_POSITIVE));
+    }
+}
@@ -0,0 +1,103 @@
+//! The content of an element kept as markup.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// The name `InnerXml` tells our serializers and deserializer apart from
+/// other newtype structs by.
+pub(crate) const INNER_XML_TOKEN: &str = "$serde_xml_rs::InnerXml";
+
+/// The content of an element, text and child elements alike, as markup.
+///
+/// Use it for fragments that are to be passed along rather than read, such
+/// as embedded XHTML. The deserializer captures everything between the start
+/// and end tags, and the serializer writes it back as is. Nothing checks that
+/// markup you create yourself is well-formed.
+///
+/// Reading with `from_slice`, the content is taken from the input exactly as
+/// it is written. Otherwise it is written out from what the parser read, with
+/// entities and character references written the way the serializer would
+/// write them and empty elements written with end tags. Child elements that
+/// use namespaces declared further up the document are written out with
+/// those declarations, so the fragment stands on its own.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::{from_str, to_string, InnerXml};
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Article {
+///     title: String,
+///     body: InnerXml,
+/// }
+/// # fn main() {
+/// let xml = "<Article><title>Hi</title><body><p>Hello <b>world</b></p></body></Article>";
+/// let article: Article = from_str(xml).unwrap();
+/// assert_eq!(article.body, InnerXml("<p>Hello <b>world</b></p>".to_string()));
+/// assert_eq!(to_string(&article).unwrap(), xml);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InnerXml(pub String);
+
+impl Deref for InnerXml {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl DerefMut for InnerXml {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.0
+    }
+}
+
+impl fmt::Display for InnerXml {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl Serialize for InnerXml {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(INNER_XML_TOKEN, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for InnerXml {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(INNER_XML_TOKEN, InnerXmlVisitor)
+    }
+}
+
+struct InnerXmlVisitor;
+
+impl<'de> de::Visitor<'de> for InnerXmlVisitor {
+    type Value = InnerXml;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the content of an element")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> ::std::result::Result<InnerXml, D::Error> {
+        String::deserialize(deserializer).map(InnerXml)
+    }
+
+    fn visit_str<E: de::Error>(self, markup: &str) -> ::std::result::Result<InnerXml, E> {
+        Ok(InnerXml(markup.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, markup: String) -> ::std::result::Result<InnerXml, E> {
+        Ok(InnerXml(markup))
+    }
+}
@@ -40,6 +40,18 @@ extern crate log;
 #[macro_use]
 extern crate serde;
 extern crate xml;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+extern crate futures_core;
+#[cfg(feature = "futures")]
+extern crate futures_io;
+#[cfg(feature = "tokio")]
+extern crate futures_sink;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
 
 #[cfg(test)]
 #[macro_use]
@@ -47,10 +59,28 @@ extern crate serde_derive;
 
 #[macro_use]
 mod error;
+mod cdata;
 pub mod de;
+pub mod element;
+mod float;
+mod inner_xml;
+mod list;
 pub mod ser;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod xsd;
 
+pub use cdata::CData;
+pub use element::{from_element, to_element, Element, Node};
 pub use error::{Error, ErrorKind};
+pub use inner_xml::InnerXml;
+pub use list::List;
 pub use xml::reader::{EventReader, ParserConfig};
 pub use ser::{to_string, to_writer, Serializer};
-pub use de::{from_reader, from_str, Deserializer};
+#[cfg(feature = "tokio")]
+pub use ser::{stream_to_tokio_writer, to_tokio_writer};
+pub use de::{from_reader, from_reader_at, from_slice, from_str, Deserializer, DeserializerConfig,
+             StreamDeserializer};
+#[cfg(feature = "tokio")]
+pub use de::{from_tokio_reader, stream_from_tokio_reader};
+#[cfg(feature = "futures")]
+pub use de::{from_futures_reader, stream_from_futures_reader};
@@ -0,0 +1,85 @@
+//! Whitespace-separated lists, as in XML Schema's `xs:list` types.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// The name `List` tells our serializer and deserializers apart from other
+/// newtype structs by.
+pub(crate) const LIST_TOKEN: &str = "$serde_xml_rs::List";
+
+/// A sequence or tuple held as whitespace-separated text, like
+/// `<coords>1.5 2.0 3.25</coords>` or `refs="a b c"`.
+///
+/// Without the wrapper, a `Vec` field takes repeated elements instead. To
+/// read every sequence in attributes and text this way, see
+/// `DeserializerConfig::xs_lists`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate serde;
+/// # extern crate serde_xml_rs;
+/// # use serde_xml_rs::{from_str, to_string, List};
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct Point {
+///     #[serde(rename = "@refs")]
+///     refs: List<Vec<String>>,
+///     coords: List<(f64, f64)>,
+/// }
+/// # fn main() {
+/// let point: Point = from_str(r#"<Point refs="a b"><coords>1.5 2</coords></Point>"#).unwrap();
+/// assert_eq!(point.coords, List((1.5, 2.0)));
+/// assert_eq!(
+///     to_string(&point).unwrap(),
+///     r#"<Point refs="a b"><coords>1.5 2</coords></Point>"#
+/// );
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct List<T>(pub T);
+
+impl<T> Deref for List<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for List<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Serialize> Serialize for List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(LIST_TOKEN, &self.0)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(LIST_TOKEN, ListVisitor(::std::marker::PhantomData))
+    }
+}
+
+struct ListVisitor<T>(::std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> de::Visitor<'de> for ListVisitor<T> {
+    type Value = List<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a whitespace-separated list")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> ::std::result::Result<List<T>, D::Error> {
+        T::deserialize(deserializer).map(List)
+    }
+}
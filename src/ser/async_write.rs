@@ -0,0 +1,162 @@
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_sink::Sink;
+use serde::ser::Serialize;
+use tokio::io::AsyncWrite;
+
+use ser::{to_writer, Serializer};
+use error::{Error, Result};
+
+/// Serializes `value` into a `tokio::io::AsyncWrite`.
+///
+/// The document is serialized into memory right away and then written out
+/// without blocking. Use `stream_to_tokio_writer` to write long sequences of
+/// items instead.
+pub fn to_tokio_writer<W, S>(writer: W, value: &S) -> WriteDocument<W>
+where
+    W: AsyncWrite + Unpin,
+    S: Serialize,
+{
+    let mut buffer = Vec::new();
+    let error = to_writer(&mut buffer, value).err();
+    WriteDocument {
+        output: Output::new(writer, buffer),
+        error,
+    }
+}
+
+/// Writes items one at a time inside a `<root>` element to a
+/// `tokio::io::AsyncWrite`.
+///
+/// Each item is wrapped in an `item` element if that is given, or named after
+/// its type otherwise, the same shape `stream_from_tokio_reader` reads back.
+/// The returned `Sink` only accepts another item once the previous one has
+/// been written, so slow writers slow down the producer instead of having
+/// the document pile up in memory. Closing the sink writes the end tag.
+///
+/// ```rust,ignore
+/// let mut entries = stream_to_tokio_writer(body, "feed", Some("entry"));
+/// for entry in export {
+///     entries.send(entry).await?;
+/// }
+/// entries.close().await?;
+/// ```
+pub fn stream_to_tokio_writer<W, T>(writer: W, root: &str, item: Option<&str>) -> WriteItems<W, T>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    WriteItems {
+        output: Output::new(writer, format!("<{}>", root).into_bytes()),
+        root: root.to_string(),
+        item: item.map(str::to_string),
+        closing: false,
+        marker: PhantomData,
+    }
+}
+
+/// A writer and the bytes still to be written to it.
+struct Output<W> {
+    writer: W,
+    buffer: Vec<u8>,
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> Output<W> {
+    fn new(writer: W, buffer: Vec<u8>) -> Self {
+        Output {
+            writer,
+            buffer,
+            written: 0,
+        }
+    }
+
+    /// Writes out the whole buffer.
+    fn poll_write_buffer(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        while self.written < self.buffer.len() {
+            let result = Pin::new(&mut self.writer).poll_write(cx, &self.buffer[self.written..]);
+            match result {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(written)) => self.written += written,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        self.buffer.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        match self.poll_write_buffer(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.writer).poll_flush(cx),
+            other => other,
+        }
+    }
+}
+
+/// A future writing a whole document, created by `to_tokio_writer`.
+pub struct WriteDocument<W> {
+    output: Output<W>,
+    error: Option<Error>,
+}
+
+impl<W: AsyncWrite + Unpin> Future for WriteDocument<W> {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if let Some(error) = this.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        this.output.poll_flush(cx).map_err(Error::from)
+    }
+}
+
+/// A sink writing items inside a root element, created by
+/// `stream_to_tokio_writer`.
+pub struct WriteItems<W, T> {
+    output: Output<W>,
+    root: String,
+    item: Option<String>,
+    closing: bool,
+    marker: PhantomData<fn(T)>,
+}
+
+impl<W: AsyncWrite + Unpin, T: Serialize> Sink<T> for WriteItems<W, T> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.get_mut().output.poll_write_buffer(cx).map_err(Error::from)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<()> {
+        // An item that fails partway mustn't leave its markup in the output
+        let this = self.get_mut();
+        let mut markup = Vec::new();
+        Serializer::new(&mut markup).write_value(this.item.clone(), &item)?;
+        this.output.buffer.extend_from_slice(&markup);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.get_mut().output.poll_flush(cx).map_err(Error::from)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if !this.closing {
+            this.closing = true;
+            let end = format!("</{}>", this.root);
+            this.output.buffer.extend_from_slice(end.as_bytes());
+        }
+        match this.output.poll_flush(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.output.writer).poll_shutdown(cx),
+            other => other,
+        }.map_err(Error::from)
+    }
+}
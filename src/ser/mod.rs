@@ -3,9 +3,24 @@ use std::fmt::Display;
 
 use serde::ser::{self, Impossible, Serialize};
 
+use xml::escape::escape_str_pcdata;
+
+use cdata::{CDataSections, CDATA_TOKEN};
+use element::{collect_start_tag, rebuild_element, root_name, StartTag, ELEMENT_TOKEN};
+use float::XsdFloat;
+use inner_xml::INNER_XML_TOKEN;
+use list::LIST_TOKEN;
 use error::{Error, ErrorKind, Result};
-use self::var::{Map, Struct};
+use self::var::{Map, Seq, Struct};
+
+pub(crate) use self::attr::AttrValueSerializer;
 
+#[cfg(feature = "tokio")]
+pub use self::async_write::{stream_to_tokio_writer, to_tokio_writer, WriteDocument, WriteItems};
+
+#[cfg(feature = "tokio")]
+mod async_write;
+mod attr;
 mod var;
 
 
@@ -37,7 +52,8 @@ mod var;
 /// ```
 pub fn to_writer<W: Write, S: Serialize>(writer: W, value: &S) -> Result<()> {
     let mut ser = Serializer::new(writer);
-    value.serialize(&mut ser)
+    ser.root = Some(root_name::<S>());
+    ser.write_value(None, value)
 }
 
 
@@ -80,6 +96,17 @@ where
     W: Write,
 {
     writer: W,
+    /// Name of the element the next value gets wrapped in, set by the struct
+    /// field or map entry that value belongs to.
+    tag: Option<String>,
+    /// Whether strings are markup to be written as is.
+    raw: bool,
+    /// The start tag of the struct about to be serialized, collected from it
+    /// by `write_value`.
+    start: Option<StartTag>,
+    /// The name a map is written as at the root, where nothing else names it,
+    /// until something else is written there.
+    root: Option<String>,
 }
 
 impl<W> Serializer<W>
@@ -87,19 +114,65 @@ where
     W: Write,
 {
     pub fn new(writer: W) -> Self {
-        Self { writer: writer }
+        Self::new_in(writer, None)
+    }
+
+    /// Creates a serializer that wraps the value in a `tag` element.
+    pub(crate) fn new_in(writer: W, tag: Option<String>) -> Self {
+        Self {
+            writer,
+            tag,
+            raw: false,
+            start: None,
+            root: None,
+        }
+    }
+
+    /// Serializes `value` in a `tag` element, having first collected the
+    /// start tag of the struct it is, so that the struct can write its start
+    /// tag right away and stream its content after.
+    fn write_value<T: ?Sized + Serialize>(&mut self, tag: Option<String>, value: &T) -> Result<()> {
+        self.start = collect_start_tag(value)?;
+        self.tag = tag;
+        value.serialize(self)
+    }
+
+    fn open_tag(&mut self) -> Result<Option<String>> {
+        self.root = None;
+        let tag = self.tag.take();
+        if let Some(ref tag) = tag {
+            write!(self.writer, "<{}>", tag)?;
+        }
+        Ok(tag)
+    }
+
+    fn close_tag(&mut self, tag: Option<String>) -> Result<()> {
+        if let Some(tag) = tag {
+            write!(self.writer, "</{}>", tag)?;
+        }
+        Ok(())
     }
 
     fn write_primitive<P: Display>(&mut self, primitive: P) -> Result<()> {
+        let tag = self.open_tag()?;
         write!(self.writer, "{}", primitive)?;
-        Ok(())
+        self.close_tag(tag)
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<()> {
+        if self.raw {
+            self.write_primitive(text)
+        } else {
+            self.write_primitive(escape_str_pcdata(text))
+        }
     }
 
     fn write_wrapped<S: Serialize>(&mut self, tag: &str, value: S) -> Result<()> {
+        let outer = self.open_tag()?;
         write!(self.writer, "<{}>", tag)?;
         value.serialize(&mut *self)?;
         write!(self.writer, "</{}>", tag)?;
-        Ok(())
+        self.close_tag(outer)
     }
 }
 
@@ -112,22 +185,20 @@ where
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
-    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = Seq<'w, W>;
+    type SerializeTuple = Seq<'w, W>;
     type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
     type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
     type SerializeMap = Map<'w, W>;
     type SerializeStruct = Struct<'w, W>;
-    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Struct<'w, W>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
         if v {
-            write!(self.writer, "true")?;
+            self.write_primitive("true")
         } else {
-            write!(self.writer, "false")?;
+            self.write_primitive("false")
         }
-
-        Ok(())
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
@@ -146,6 +217,10 @@ where
         self.write_primitive(v)
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        self.write_primitive(v)
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
         self.write_primitive(v)
     }
@@ -162,20 +237,24 @@ where
         self.write_primitive(v)
     }
 
-    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
         self.write_primitive(v)
     }
 
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        self.write_primitive(XsdFloat(v))
+    }
+
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        self.write_primitive(XsdFloat(v))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok> {
-        self.write_primitive(v)
+        self.write_text(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-        self.write_primitive(value)
+        self.write_text(value)
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
@@ -187,7 +266,8 @@ where
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        Ok(())
+        let tag = self.open_tag()?;
+        self.close_tag(tag)
     }
 
     fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
@@ -199,7 +279,11 @@ where
     }
 
     fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok> {
-        self.write_wrapped(name, ())
+        if self.tag.is_some() {
+            self.serialize_unit()
+        } else {
+            self.write_wrapped(name, ())
+        }
     }
 
     fn serialize_unit_variant(
@@ -208,9 +292,11 @@ where
         variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok> {
-        Err(
-            ErrorKind::UnsupportedOperation("serialize_unit_variant".to_string()).into(),
-        )
+        if self.tag.is_some() {
+            self.write_primitive(variant)
+        } else {
+            self.write_wrapped(variant, ())
+        }
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
@@ -218,9 +304,32 @@ where
         name: &'static str,
         value: &T,
     ) -> Result<Self::Ok> {
-        Err(
-            ErrorKind::UnsupportedOperation("serialize_newtype_struct".to_string()).into(),
-        )
+        if name == ELEMENT_TOKEN {
+            // The element has a name of its own
+            let element = rebuild_element(value)?;
+            self.tag = None;
+            write!(self.writer, "{}", element)?;
+            Ok(())
+        } else if name == LIST_TOKEN {
+            match value.serialize(AttrValueSerializer)? {
+                Some(text) => self.write_text(&text),
+                None => self.serialize_none(),
+            }
+        } else if name == INNER_XML_TOKEN {
+            self.raw = true;
+            let result = value.serialize(&mut *self);
+            self.raw = false;
+            result
+        } else if name == CDATA_TOKEN {
+            match value.serialize(AttrValueSerializer)? {
+                Some(text) => self.write_primitive(CDataSections(&text)),
+                None => self.serialize_none(),
+            }
+        } else {
+            Err(
+                ErrorKind::UnsupportedOperation("serialize_newtype_struct".to_string()).into(),
+            )
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
@@ -230,20 +339,21 @@ where
         variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok> {
-        self.write_wrapped(variant, value)
+        if variant == "$value" {
+            value.serialize(self)
+        } else {
+            self.write_wrapped(variant, value)
+        }
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        // TODO: Figure out how to constrain the things written to only be composites
-        Err(
-            ErrorKind::UnsupportedOperation("serialize_seq".to_string()).into(),
-        )
+        self.root = None;
+        let tag = self.tag.take();
+        Ok(Seq::new(self, tag))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
-        Err(
-            ErrorKind::UnsupportedOperation("serialize_tuple".to_string()).into(),
-        )
+        self.serialize_seq(Some(len))
     }
 
     fn serialize_tuple_struct(
@@ -269,12 +379,22 @@ where
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        Ok(Map::new(self))
+        match self.tag.take().or_else(|| self.root.take()) {
+            Some(tag) => {
+                write!(self.writer, "<{}", tag)?;
+                let start = self.start.take();
+                Ok(Map::with_attributes(self, &tag, start))
+            },
+            None => Ok(Map::new(self)),
+        }
     }
 
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        write!(self.writer, "<{}>", name)?;
-        Ok(Struct::new(self, name))
+        self.root = None;
+        let tag = self.tag.take().unwrap_or_else(|| name.to_string());
+        write!(self.writer, "<{}", tag)?;
+        let start = self.start.take();
+        Ok(Struct::with_attributes(self, &tag, None, start))
     }
 
     fn serialize_struct_variant(
@@ -284,7 +404,10 @@ where
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(ErrorKind::UnsupportedOperation("Result".to_string()).into())
+        let outer = self.open_tag()?;
+        write!(self.writer, "<{}", variant)?;
+        let start = self.start.take();
+        Ok(Struct::with_attributes(self, variant, outer, start))
     }
 }
 
@@ -322,7 +445,7 @@ mod tests {
         }
 
         let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, "<foo>");
+        assert_eq!(got, "<foo");
     }
 
     #[test]
@@ -363,6 +486,82 @@ mod tests {
         assert_eq!(got, should_be);
     }
 
+    #[test]
+    fn test_serialize_struct_streams_content() {
+        // Keeps every chunk written, to show that the content of a struct
+        // isn't held back until the struct ends
+        struct Chunks(Vec<String>);
+
+        impl Write for Chunks {
+            fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+                self.0.push(String::from_utf8_lossy(buf).into_owned());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> ::std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Item {
+            name: String,
+            #[serde(rename = "@id")]
+            id: u32,
+        }
+
+        #[derive(Serialize)]
+        struct Items {
+            item: Vec<Item>,
+        }
+
+        let items = Items {
+            item: vec![
+                Item { name: "a".to_string(), id: 1 },
+                Item { name: "b".to_string(), id: 2 },
+            ],
+        };
+        let mut chunks = Chunks(Vec::new());
+        to_writer(&mut chunks, &items).unwrap();
+        assert_eq!(
+            chunks.0.concat(),
+            r#"<Items><item id="1"><name>a</name></item><item id="2"><name>b</name></item></Items>"#
+        );
+        assert!(!chunks.0.iter().any(|chunk| chunk.contains("<name>") && chunk.contains("</item>")));
+    }
+
+    #[test]
+    fn test_serialize_nested_struct_and_seq() {
+        #[derive(Serialize)]
+        struct Pet {
+            name: String,
+        }
+
+        #[derive(Serialize)]
+        struct Person {
+            pet: Pet,
+            nickname: Vec<String>,
+        }
+
+        let bob = Person {
+            pet: Pet {
+                name: "Rex".to_string(),
+            },
+            nickname: vec!["Bobby".to_string(), "B & B".to_string()],
+        };
+        let should_be = "<Person><pet><name>Rex</name></pet>\
+                         <nickname>Bobby</nickname><nickname>B &amp; B</nickname></Person>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            bob.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
     #[test]
     fn test_serialize_map_entries() {
         let should_be = "<name>Bob</name><age>5</age>";
@@ -379,6 +578,24 @@ mod tests {
         assert_eq!(got, should_be);
     }
 
+    #[test]
+    fn test_serialize_map_keys_and_values() {
+        let should_be = "<name>Bob</name><age>5</age>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            let mut map = Map::new(&mut ser);
+            map.serialize_key("name").unwrap();
+            map.serialize_value("Bob").unwrap();
+            map.serialize_key("age").unwrap();
+            map.serialize_value(&5).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
     #[test]
     fn test_serialize_enum() {
         #[derive(Serialize)]
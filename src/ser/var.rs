@@ -2,23 +2,54 @@ use std::io::Write;
 
 use serde::ser::{self, Serialize};
 
+use element::{map_key, no_element, other_element, Attributes, Field, Markup, StartTag};
 use ser::Serializer;
-use error::{Error, Result};
+use error::{Error, ErrorKind, Result};
 
 /// An implementation of `SerializeMap` for serializing to XML.
+///
+/// Entries are written the same way as the fields of a struct, in the
+/// element the map was assigned to if there is one. Structs with flattened
+/// fields are serialized as maps, so `@name` keys are attributes.
 pub struct Map<'w, W>
 where
     W: 'w + Write,
 {
-    parent: &'w mut Serializer<W>,
+    entries: Entries<'w, W>,
+    next_key: Option<String>,
+}
+
+enum Entries<'w, W>
+where
+    W: 'w + Write,
+{
+    Element(Struct<'w, W>),
+    Inline(&'w mut Serializer<W>),
 }
 
 impl<'w, W> Map<'w, W>
 where
     W: 'w + Write,
 {
+    /// Creates a map whose entries are written inline.
     pub fn new(parent: &'w mut Serializer<W>) -> Map<'w, W> {
-        Map { parent }
+        Map {
+            entries: Entries::Inline(parent),
+            next_key: None,
+        }
+    }
+
+    /// Creates a map written as the element `name`, whose start tag has only
+    /// been written up to its name, like `Struct::with_attributes`.
+    pub(crate) fn with_attributes(
+        parent: &'w mut Serializer<W>,
+        name: &str,
+        collected: Option<StartTag>,
+    ) -> Map<'w, W> {
+        Map {
+            entries: Entries::Element(Struct::with_attributes(parent, name, None, collected)),
+            next_key: None,
+        }
     }
 }
 
@@ -29,53 +60,139 @@ where
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized + Serialize>(&mut self, _: &T) -> Result<()> {
-        panic!("impossible to serialize the key on its own, please use serialize_entry()")
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(map_key(key)?);
+        Ok(())
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.parent)
+        let key = self.next_key.take().unwrap_or_default();
+        match self.entries {
+            Entries::Element(ref mut element) => element.serialize_entry(&key, value),
+            Entries::Inline(ref mut parent) => match Field::new(&key) {
+                Field::Content(tag) => parent.write_value(tag.map(str::to_string), value),
+                _ => no_element(&key),
+            },
+        }
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
-    }
-
-    fn serialize_entry<K: ?Sized + Serialize, V: ?Sized + Serialize>(
-        &mut self,
-        key: &K,
-        value: &V,
-    ) -> Result<()> {
-        // TODO: Is it possible to ensure our key is never a composite type?
-        // Anything which isn't a "primitive" would lead to malformed XML here...
-        write!(self.parent.writer, "<")?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer, ">")?;
-
-        value.serialize(&mut *self.parent)?;
-
-        write!(self.parent.writer, "</")?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer, ">")?;
-        Ok(())
+        match self.entries {
+            Entries::Element(element) => ser::SerializeStruct::end(element),
+            Entries::Inline(_) => Ok(()),
+        }
     }
 }
 
 /// An implementation of `SerializeStruct` for serializing to XML.
+///
+/// Fields renamed to `@name` are written as attributes of the start tag, and
+/// left out when they are `None`. With the start tag collected from the
+/// struct beforehand, as `Serializer` does, they may come anywhere, and are
+/// skipped here; otherwise they have to come before the content.
 pub struct Struct<'w, W>
 where
     W: 'w + Write,
 {
     parent: &'w mut Serializer<W>,
-    name: &'w str,
+    name: String,
+    outer: Option<String>,
+    /// The start tag while it still lacks its attributes and closing `>`.
+    start: Option<StartTag>,
+    /// Whether the start tag was collected beforehand, with the attributes
+    /// of every field.
+    collected: bool,
 }
 
 impl<'w, W> Struct<'w, W>
 where
     W: 'w + Write,
 {
-    pub fn new(parent: &'w mut Serializer<W>, name: &'w str) -> Struct<'w, W> {
-        Struct { parent, name }
+    pub fn new(parent: &'w mut Serializer<W>, name: &str) -> Struct<'w, W> {
+        Struct::new_in(parent, name, None)
+    }
+
+    /// Creates a struct that is itself nested in the already opened element
+    /// `outer`, as struct variants are.
+    pub fn new_in(
+        parent: &'w mut Serializer<W>,
+        name: &str,
+        outer: Option<String>,
+    ) -> Struct<'w, W> {
+        Struct {
+            parent,
+            name: name.to_string(),
+            outer,
+            start: None,
+            collected: false,
+        }
+    }
+
+    /// Like `new_in`, for a struct whose start tag has only been written up
+    /// to its name, so that attributes can still be added, unless they were
+    /// `collected` already.
+    pub(crate) fn with_attributes(
+        parent: &'w mut Serializer<W>,
+        name: &str,
+        outer: Option<String>,
+        collected: Option<StartTag>,
+    ) -> Struct<'w, W> {
+        Struct {
+            collected: collected.is_some(),
+            start: Some(collected.unwrap_or_else(|| StartTag::new(name))),
+            ..Struct::new_in(parent, name, outer)
+        }
+    }
+
+    /// Returns the start tag for `field` to add attributes to, unless it was
+    /// written already.
+    fn start_tag(&mut self, field: &str) -> Result<&mut StartTag> {
+        match self.start {
+            Some(ref mut start) => Ok(start),
+            None => {
+                let message = format!("{} follows the start tag of {}", field, self.name);
+                Err(ErrorKind::Custom(message).into())
+            },
+        }
+    }
+
+    /// Writes the rest of the start tag, if it is still open.
+    fn close_start_tag(&mut self) -> Result<()> {
+        if let Some(start) = self.start.take() {
+            write!(self.parent.writer, "{}>", Attributes(&start.finish()))?;
+        }
+        Ok(())
+    }
+
+    /// Writes back the attributes and children a `$other` field collected:
+    /// the attributes into the start tag, and the children where the field
+    /// is.
+    fn write_other<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let other = other_element(value)?;
+        let children = if self.collected || (other.attributes.is_empty() && other.namespaces.is_empty()) {
+            other.children
+        } else {
+            self.start_tag("$other")?.add_other(other)
+        };
+        if !children.is_empty() {
+            self.close_start_tag()?;
+            write!(self.parent.writer, "{}", Markup(&children))?;
+        }
+        Ok(())
+    }
+
+    /// Writes a field, or a map entry, where its name says it goes.
+    fn serialize_entry<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        match Field::new(key) {
+            Field::Attribute(_) | Field::Attributes if self.collected => Ok(()),
+            Field::Attribute(name) => self.start_tag(key)?.add_field(name, value),
+            Field::Attributes => self.start_tag(key)?.add_map(value),
+            Field::Other => self.write_other(value),
+            Field::Content(tag) => {
+                self.close_start_tag()?;
+                self.parent.write_value(tag.map(str::to_string), value)
+            },
+        }
     }
 }
 
@@ -91,13 +208,85 @@ where
         key: &'static str,
         value: &T,
     ) -> Result<()> {
-        write!(self.parent.writer, "<{}>", key)?;
-        value.serialize(&mut *self.parent)?;
-        write!(self.parent.writer, "</{}>", key)?;
+        self.serialize_entry(key, value)
+    }
+
+    fn end(mut self) -> Result<Self::Ok> {
+        self.close_start_tag()?;
+        write!(self.parent.writer, "</{}>", self.name)?;
+        self.parent.close_tag(self.outer)
+    }
+}
+
+impl<'w, W> ser::SerializeStructVariant for Struct<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+/// An implementation of `SerializeSeq` for serializing to XML.
+///
+/// Every element is wrapped in its own copy of the element the sequence was
+/// assigned to, the same shape `de::SeqAccess` reads back.
+pub struct Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    parent: &'w mut Serializer<W>,
+    tag: Option<String>,
+}
+
+impl<'w, W> Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    pub fn new(parent: &'w mut Serializer<W>, tag: Option<String>) -> Seq<'w, W> {
+        Seq { parent, tag }
+    }
+}
+
+impl<'w, W> ser::SerializeSeq for Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.parent.write_value(self.tag.clone(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
         Ok(())
     }
+}
+
+impl<'w, W> ser::SerializeTuple for Seq<'w, W>
+where
+    W: 'w + Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
 
     fn end(self) -> Result<Self::Ok> {
-        write!(self.parent.writer, "</{}>", self.name).map_err(|e| e.into())
+        ser::SerializeSeq::end(self)
     }
 }
@@ -0,0 +1,158 @@
+//! `chrono` types as XML Schema dates, times and durations.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(rename = "@start", with = "serde_xml_rs::xsd::chrono::date_time")]
+//!     start: DateTime<FixedOffset>,
+//!     #[serde(with = "serde_xml_rs::xsd::chrono::duration")]
+//!     length: TimeDelta,
+//! }
+//! ```
+
+use ::chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use xsd::{self, Date, Offset, Time};
+
+fn to_date((year, month, day): Date) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn to_time((hour, minute, second, nanos): Time) -> Option<NaiveTime> {
+    NaiveTime::from_hms_nano_opt(hour, minute, second, nanos)
+}
+
+fn from_date(date: &NaiveDate) -> Date {
+    (date.year(), date.month(), date.day())
+}
+
+fn from_time(time: &NaiveTime) -> Time {
+    (time.hour(), time.minute(), time.second(), time.nanosecond())
+}
+
+fn to_date_time(text: &str) -> Option<(NaiveDateTime, Option<Offset>)> {
+    let (date, time, offset) = xsd::parse_date_time(text)?;
+    Some((NaiveDateTime::new(to_date(date)?, to_time(time)?), offset))
+}
+
+/// `DateTime<FixedOffset>` as an `xs:dateTime`. Values without a timezone
+/// are taken to be in UTC.
+pub mod date_time {
+    use ::chrono::{DateTime, FixedOffset, TimeZone};
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(
+        value: &DateTime<FixedOffset>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let offset = value.offset().local_minus_utc();
+        let text = xsd::format_date_time(
+            super::from_date(&value.date_naive()),
+            super::from_time(&value.time()),
+            Some(offset),
+        );
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<FixedOffset>, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:dateTime", |text| {
+            let (local, offset) = super::to_date_time(text)?;
+            FixedOffset::east_opt(offset.unwrap_or(0))?
+                .from_local_datetime(&local)
+                .single()
+        })
+    }
+}
+
+/// `NaiveDateTime` as an `xs:dateTime` without a timezone. A timezone in
+/// the input is ignored.
+pub mod naive_date_time {
+    use ::chrono::NaiveDateTime;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = xsd::format_date_time(
+            super::from_date(&value.date()),
+            super::from_time(&value.time()),
+            None,
+        );
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:dateTime", |text| {
+            super::to_date_time(text).map(|(local, _)| local)
+        })
+    }
+}
+
+/// `NaiveDate` as an `xs:date`. A timezone in the input is ignored.
+pub mod date {
+    use ::chrono::NaiveDate;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&xsd::format_date(super::from_date(value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:date", |text| {
+            super::to_date(xsd::parse_date(text)?.0)
+        })
+    }
+}
+
+/// `NaiveTime` as an `xs:time`. A timezone in the input is ignored.
+pub mod time_of_day {
+    use ::chrono::NaiveTime;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&xsd::format_time(super::from_time(value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:time", |text| {
+            super::to_time(xsd::parse_time(text)?.0)
+        })
+    }
+}
+
+/// `TimeDelta` as an `xs:duration` in days, hours, minutes and seconds.
+pub mod duration {
+    use std::convert::TryFrom;
+
+    use ::chrono::TimeDelta;
+    use serde::{Deserializer, Serializer};
+
+    use xsd::{self, Duration};
+
+    pub fn serialize<S: Serializer>(value: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error> {
+        let magnitude = value.abs();
+        let duration = Duration {
+            negative: *value < TimeDelta::zero(),
+            seconds: magnitude.num_seconds() as u64,
+            nanos: magnitude.subsec_nanos() as u32,
+        };
+        serializer.serialize_str(&xsd::format_duration(&duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimeDelta, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:duration in days or less", |text| {
+            let duration = xsd::parse_duration(text)?;
+            let seconds = i64::try_from(duration.seconds).ok()?;
+            let magnitude = TimeDelta::new(seconds, duration.nanos)?;
+            Some(if duration.negative { -magnitude } else { magnitude })
+        })
+    }
+}
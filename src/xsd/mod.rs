@@ -0,0 +1,402 @@
+//! Helpers for the XML Schema date, time and duration types, to use with
+//! `#[serde(with = "...")]` on fields of `chrono` or `time` types.
+//!
+//! They read and write the `xs:dateTime`, `xs:date`, `xs:time` and
+//! `xs:duration` lexical forms, such as `2026-10-18T12:00:00Z` or `P1DT2H`,
+//! in element text as well as in attributes.
+//!
+//! Enable the `chrono` feature for `xsd::chrono` and the `time` feature for
+//! `xsd::time`.
+
+use std::fmt::{self, Write};
+
+use serde::de::{self, Deserialize, Deserializer, Unexpected};
+
+#[cfg(feature = "chrono")]
+pub mod chrono;
+#[cfg(feature = "time")]
+pub mod time;
+
+/// A calendar date: year, month and day.
+pub(crate) type Date = (i32, u32, u32);
+
+/// A time of day: hour, minute, second and nanosecond.
+pub(crate) type Time = (u32, u32, u32, u32);
+
+/// A timezone, in seconds east of UTC.
+pub(crate) type Offset = i32;
+
+/// A duration as a sign, whole seconds and nanoseconds.
+pub(crate) struct Duration {
+    pub negative: bool,
+    pub seconds: u64,
+    pub nanos: u32,
+}
+
+/// Deserializes a string and parses it with `parse`, which returns `None`
+/// for text that isn't `expected`.
+pub(crate) fn deserialize_text<'de, D, T, F>(
+    deserializer: D,
+    expected: &str,
+    parse: F,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Option<T>,
+{
+    let text = String::deserialize(deserializer)?;
+    let trimmed = text.trim();
+    parse(trimmed).ok_or_else(|| de::Error::invalid_value(Unexpected::Str(trimmed), &expected))
+}
+
+/// Reads `text` a few characters at a time.
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Reads a run of digits; at least `min` of them, and no more than
+    /// `max` if given.
+    fn digits(&mut self, min: usize, max: Option<usize>) -> Option<&'a str> {
+        let len = self.rest.bytes().take_while(u8::is_ascii_digit).count();
+        if len < min || max.is_some_and(|max| len > max) {
+            return None;
+        }
+        let (digits, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(digits)
+    }
+
+    fn number(&mut self, len: usize) -> Option<u32> {
+        self.digits(len, Some(len))?.parse().ok()
+    }
+
+    /// Reads the digits after a decimal point as nanoseconds.
+    fn fraction(&mut self) -> Option<u32> {
+        if !self.eat('.') {
+            return Some(0);
+        }
+        let digits = self.digits(1, None)?;
+        let nanos = digits.bytes().chain(std::iter::repeat(b'0')).take(9);
+        Some(nanos.fold(0, |nanos, digit| nanos * 10 + u32::from(digit - b'0')))
+    }
+
+    fn end(self) -> Option<()> {
+        if self.rest.is_empty() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn date(&mut self) -> Option<Date> {
+        let negative = self.eat('-');
+        let year = self.digits(4, None)?;
+        if year.len() > 4 && year.starts_with('0') {
+            return None;
+        }
+        let year: i32 = year.parse().ok()?;
+        let year = if negative { -year } else { year };
+        if !self.eat('-') {
+            return None;
+        }
+        let month = self.number(2)?;
+        if !self.eat('-') {
+            return None;
+        }
+        Some((year, month, self.number(2)?))
+    }
+
+    fn time(&mut self) -> Option<Time> {
+        let hour = self.number(2)?;
+        if !self.eat(':') {
+            return None;
+        }
+        let minute = self.number(2)?;
+        if !self.eat(':') {
+            return None;
+        }
+        let second = self.number(2)?;
+        Some((hour, minute, second, self.fraction()?))
+    }
+
+    fn offset(&mut self) -> Option<Option<Offset>> {
+        if self.rest.is_empty() {
+            return Some(None);
+        }
+        if self.eat('Z') {
+            return Some(Some(0));
+        }
+        let sign = if self.eat('+') {
+            1
+        } else if self.eat('-') {
+            -1
+        } else {
+            return None;
+        };
+        let hours = self.number(2)?;
+        if !self.eat(':') {
+            return None;
+        }
+        let minutes = self.number(2)?;
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        Some(Some(sign * (hours * 3600 + minutes * 60) as i32))
+    }
+}
+
+/// Parses an `xs:dateTime` such as `2026-10-18T12:00:00.5+02:00`.
+pub(crate) fn parse_date_time(text: &str) -> Option<(Date, Time, Option<Offset>)> {
+    let mut cursor = Cursor { rest: text };
+    let date = cursor.date()?;
+    if !cursor.eat('T') {
+        return None;
+    }
+    let time = cursor.time()?;
+    let offset = cursor.offset()?;
+    cursor.end()?;
+    Some((date, time, offset))
+}
+
+/// Parses an `xs:date` such as `2026-10-18`.
+pub(crate) fn parse_date(text: &str) -> Option<(Date, Option<Offset>)> {
+    let mut cursor = Cursor { rest: text };
+    let date = cursor.date()?;
+    let offset = cursor.offset()?;
+    cursor.end()?;
+    Some((date, offset))
+}
+
+/// Parses an `xs:time` such as `12:00:00Z`.
+pub(crate) fn parse_time(text: &str) -> Option<(Time, Option<Offset>)> {
+    let mut cursor = Cursor { rest: text };
+    let time = cursor.time()?;
+    let offset = cursor.offset()?;
+    cursor.end()?;
+    Some((time, offset))
+}
+
+/// Parses an `xs:duration` such as `-P1DT2H30.5S`.
+///
+/// Years and months have no fixed length, so durations using them are
+/// rejected unless they are zero.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    let mut cursor = Cursor { rest: text };
+    let negative = cursor.eat('-');
+    if !cursor.eat('P') {
+        return None;
+    }
+    let mut seconds: u64 = 0;
+    let mut nanos = 0;
+    let mut any = false;
+    let mut in_time = false;
+    // Designators in the order they must appear, with their length in
+    // seconds; `None` for those without a fixed length
+    let designators = [
+        ('Y', None, false),
+        ('M', None, false),
+        ('D', Some(86_400), false),
+        ('H', Some(3_600), true),
+        ('M', Some(60), true),
+        ('S', Some(1), true),
+    ];
+    let mut next = 0;
+    while !cursor.rest.is_empty() {
+        if !in_time && cursor.eat('T') {
+            in_time = true;
+            if cursor.rest.is_empty() {
+                return None;
+            }
+            continue;
+        }
+        let value: u64 = cursor.digits(1, None)?.parse().ok()?;
+        let fraction = if cursor.rest.starts_with('.') {
+            Some(cursor.fraction()?)
+        } else {
+            None
+        };
+        let designator = cursor.rest.chars().next()?;
+        cursor.rest = &cursor.rest[designator.len_utf8()..];
+        let index = designators[next..].iter().position(|&(name, _, time)| {
+            name == designator && time == in_time
+        })?;
+        next += index + 1;
+        let (_, length, _) = designators[next - 1];
+        if fraction.is_some() && designator != 'S' {
+            return None;
+        }
+        match length {
+            Some(length) => seconds = seconds.checked_add(value.checked_mul(length)?)?,
+            None if value == 0 => {},
+            None => return None,
+        }
+        nanos = fraction.unwrap_or(0);
+        any = true;
+    }
+    if !any {
+        return None;
+    }
+    Some(Duration {
+        negative,
+        seconds,
+        nanos,
+    })
+}
+
+fn write_fraction(out: &mut String, nanos: u32) {
+    if nanos > 0 {
+        let fraction = format!("{:09}", nanos);
+        out.push('.');
+        out.push_str(fraction.trim_end_matches('0'));
+    }
+}
+
+fn write_date(out: &mut String, (year, month, day): Date) -> fmt::Result {
+    if year < 0 {
+        out.push('-');
+    }
+    write!(out, "{:04}-{:02}-{:02}", year.unsigned_abs(), month, day)
+}
+
+fn write_time(out: &mut String, (hour, minute, second, nanos): Time) -> fmt::Result {
+    write!(out, "{:02}:{:02}:{:02}", hour, minute, second)?;
+    write_fraction(out, nanos);
+    Ok(())
+}
+
+fn write_offset(out: &mut String, offset: Option<Offset>) -> fmt::Result {
+    match offset {
+        None => Ok(()),
+        Some(0) => {
+            out.push('Z');
+            Ok(())
+        },
+        Some(offset) => {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let minutes = offset.unsigned_abs() / 60;
+            write!(out, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+        },
+    }
+}
+
+/// Writes an `xs:dateTime`, with the fraction of a second only if there is
+/// one.
+pub(crate) fn format_date_time(date: Date, time: Time, offset: Option<Offset>) -> String {
+    let mut out = String::new();
+    let _ = write_date(&mut out, date);
+    out.push('T');
+    let _ = write_time(&mut out, time);
+    let _ = write_offset(&mut out, offset);
+    out
+}
+
+/// Writes an `xs:date`.
+pub(crate) fn format_date(date: Date) -> String {
+    let mut out = String::new();
+    let _ = write_date(&mut out, date);
+    out
+}
+
+/// Writes an `xs:time`.
+pub(crate) fn format_time(time: Time) -> String {
+    let mut out = String::new();
+    let _ = write_time(&mut out, time);
+    out
+}
+
+/// Writes an `xs:duration` in days, hours, minutes and seconds, such as
+/// `P1DT2H`.
+pub(crate) fn format_duration(duration: &Duration) -> String {
+    let mut out = String::new();
+    if duration.negative && (duration.seconds > 0 || duration.nanos > 0) {
+        out.push('-');
+    }
+    out.push('P');
+    let days = duration.seconds / 86_400;
+    let hours = duration.seconds / 3_600 % 24;
+    let minutes = duration.seconds / 60 % 60;
+    let seconds = duration.seconds % 60;
+    if days > 0 {
+        let _ = write!(out, "{}D", days);
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || duration.nanos > 0 || days == 0 {
+        out.push('T');
+        if hours > 0 {
+            let _ = write!(out, "{}H", hours);
+        }
+        if minutes > 0 {
+            let _ = write!(out, "{}M", minutes);
+        }
+        if seconds > 0 || duration.nanos > 0 || out.ends_with('T') {
+            let _ = write!(out, "{}", seconds);
+            write_fraction(&mut out, duration.nanos);
+            out.push('S');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_time() {
+        assert_eq!(
+            parse_date_time("2026-10-18T12:00:00Z"),
+            Some(((2026, 10, 18), (12, 0, 0, 0), Some(0)))
+        );
+        assert_eq!(
+            parse_date_time("-0044-03-15T09:30:05.25-05:30"),
+            Some(((-44, 3, 15), (9, 30, 5, 250_000_000), Some(-19_800)))
+        );
+        assert_eq!(
+            parse_date_time("2026-10-18T12:00:00"),
+            Some(((2026, 10, 18), (12, 0, 0, 0), None))
+        );
+        for text in &["2026-10-18", "2026-10-18T12:00", "26-10-18T12:00:00", "2026-10-18T12:00:00+2"] {
+            assert_eq!(parse_date_time(text), None, "{}", text);
+        }
+
+        assert_eq!(
+            format_date_time((2026, 10, 18), (12, 0, 0, 500_000_000), Some(7200)),
+            "2026-10-18T12:00:00.5+02:00"
+        );
+        assert_eq!(format_date((-44, 3, 15)), "-0044-03-15");
+        assert_eq!(format_time((9, 5, 0, 0)), "09:05:00");
+    }
+
+    #[test]
+    fn test_duration() {
+        let seconds = |text| parse_duration(text).map(|d| (d.negative, d.seconds, d.nanos));
+        assert_eq!(seconds("P1DT2H"), Some((false, 93_600, 0)));
+        assert_eq!(seconds("-PT1M30.5S"), Some((true, 90, 500_000_000)));
+        assert_eq!(seconds("P0Y0M2D"), Some((false, 172_800, 0)));
+        for text in &["P", "PT", "P1M", "P1H", "PT1D", "P1DT", "PT1.5M", "P2D1D", "1D"] {
+            assert_eq!(seconds(text), None, "{}", text);
+        }
+
+        let format = |negative, seconds, nanos| {
+            format_duration(&Duration {
+                negative,
+                seconds,
+                nanos,
+            })
+        };
+        assert_eq!(format(false, 93_600, 0), "P1DT2H");
+        assert_eq!(format(true, 90, 500_000_000), "-PT1M30.5S");
+        assert_eq!(format(false, 86_400, 0), "P1D");
+        assert_eq!(format(true, 0, 0), "PT0S");
+    }
+}
@@ -0,0 +1,167 @@
+//! `time` types as XML Schema dates, times and durations.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(rename = "@start", with = "serde_xml_rs::xsd::time::offset_date_time")]
+//!     start: OffsetDateTime,
+//!     #[serde(with = "serde_xml_rs::xsd::time::duration")]
+//!     length: Duration,
+//! }
+//! ```
+
+use std::convert::TryFrom;
+
+use ::time::{Date as TimeDate, Month, PrimitiveDateTime, Time as TimeOfDay};
+
+use xsd::{self, Date, Offset, Time};
+
+fn to_date((year, month, day): Date) -> Option<TimeDate> {
+    let month = Month::try_from(u8::try_from(month).ok()?).ok()?;
+    TimeDate::from_calendar_date(year, month, u8::try_from(day).ok()?).ok()
+}
+
+fn to_time((hour, minute, second, nanos): Time) -> Option<TimeOfDay> {
+    let hour = u8::try_from(hour).ok()?;
+    let minute = u8::try_from(minute).ok()?;
+    let second = u8::try_from(second).ok()?;
+    TimeOfDay::from_hms_nano(hour, minute, second, nanos).ok()
+}
+
+fn from_date(date: TimeDate) -> Date {
+    (date.year(), u32::from(u8::from(date.month())), u32::from(date.day()))
+}
+
+fn from_time(time: TimeOfDay) -> Time {
+    (
+        u32::from(time.hour()),
+        u32::from(time.minute()),
+        u32::from(time.second()),
+        time.nanosecond(),
+    )
+}
+
+fn to_date_time(text: &str) -> Option<(PrimitiveDateTime, Option<Offset>)> {
+    let (date, time, offset) = xsd::parse_date_time(text)?;
+    Some((PrimitiveDateTime::new(to_date(date)?, to_time(time)?), offset))
+}
+
+/// `OffsetDateTime` as an `xs:dateTime`. Values without a timezone are
+/// taken to be in UTC.
+pub mod offset_date_time {
+    use ::time::{OffsetDateTime, UtcOffset};
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = xsd::format_date_time(
+            super::from_date(value.date()),
+            super::from_time(value.time()),
+            Some(value.offset().whole_seconds()),
+        );
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OffsetDateTime, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:dateTime", |text| {
+            let (local, offset) = super::to_date_time(text)?;
+            let offset = UtcOffset::from_whole_seconds(offset.unwrap_or(0)).ok()?;
+            Some(local.assume_offset(offset))
+        })
+    }
+}
+
+/// `PrimitiveDateTime` as an `xs:dateTime` without a timezone. A timezone
+/// in the input is ignored.
+pub mod primitive_date_time {
+    use ::time::PrimitiveDateTime;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(
+        value: &PrimitiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let text = xsd::format_date_time(
+            super::from_date(value.date()),
+            super::from_time(value.time()),
+            None,
+        );
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PrimitiveDateTime, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:dateTime", |text| {
+            super::to_date_time(text).map(|(local, _)| local)
+        })
+    }
+}
+
+/// `Date` as an `xs:date`. A timezone in the input is ignored.
+pub mod date {
+    use ::time::Date;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&xsd::format_date(super::from_date(*value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:date", |text| {
+            super::to_date(xsd::parse_date(text)?.0)
+        })
+    }
+}
+
+/// `Time` as an `xs:time`. A timezone in the input is ignored.
+pub mod time_of_day {
+    use ::time::Time;
+    use serde::{Deserializer, Serializer};
+
+    use xsd;
+
+    pub fn serialize<S: Serializer>(value: &Time, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&xsd::format_time(super::from_time(*value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Time, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:time", |text| {
+            super::to_time(xsd::parse_time(text)?.0)
+        })
+    }
+}
+
+/// `Duration` as an `xs:duration` in days, hours, minutes and seconds.
+pub mod duration {
+    use std::convert::TryFrom;
+
+    use ::time::Duration as TimeDuration;
+    use serde::{Deserializer, Serializer};
+
+    use xsd::{self, Duration};
+
+    pub fn serialize<S: Serializer>(value: &TimeDuration, serializer: S) -> Result<S::Ok, S::Error> {
+        let magnitude = value.abs();
+        let duration = Duration {
+            negative: value.is_negative(),
+            seconds: magnitude.whole_seconds() as u64,
+            nanos: magnitude.subsec_nanoseconds() as u32,
+        };
+        serializer.serialize_str(&xsd::format_duration(&duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimeDuration, D::Error> {
+        xsd::deserialize_text(deserializer, "an xs:duration in days or less", |text| {
+            let duration = xsd::parse_duration(text)?;
+            let seconds = i64::try_from(duration.seconds).ok()?;
+            let magnitude = TimeDuration::new(seconds, duration.nanos as i32);
+            Some(if duration.negative { -magnitude } else { magnitude })
+        })
+    }
+}
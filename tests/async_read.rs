@@ -0,0 +1,139 @@
+#![cfg(all(feature = "tokio", feature = "futures"))]
+
+#[macro_use]
+extern crate serde_derive;
+extern crate futures;
+extern crate serde_xml_rs;
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::executor::{block_on, block_on_stream};
+use futures::io::AsyncRead;
+use serde_xml_rs::{from_futures_reader, from_tokio_reader, stream_from_futures_reader,
+                   stream_from_tokio_reader, DeserializerConfig, ErrorKind};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Entry {
+    id: u32,
+    title: String,
+}
+
+const FEED: &str = r##"<?xml version="1.0"?>
+<!-- a feed -->
+<a:feed xmlns:a="urn:feed">
+    <a:title>News</a:title>
+    <a:entry id="1"><a:title><![CDATA[</a:entry>]]></a:title></a:entry>
+    <a:entry id="2" title="two" />
+    <a:entry id="3"><a:title>three &amp; more</a:title></a:entry>
+</a:feed>"##;
+
+/// Hands out a few bytes at a time, returning `Pending` in between.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    ready: bool,
+}
+
+impl<'a> AsyncRead for Trickle<'a> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        let len = buf.len().min(self.bytes.len()).min(3);
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes = &self.bytes[len..];
+        Poll::Ready(Ok(len))
+    }
+}
+
+fn titles<I: Iterator<Item = Result<Entry, serde_xml_rs::Error>>>(entries: I) -> Vec<String> {
+    entries.map(|entry| entry.unwrap().title).collect()
+}
+
+#[test]
+fn stream_of_items_from_futures_reader() {
+    let reader = Trickle {
+        bytes: FEED.as_bytes(),
+        ready: false,
+    };
+    let entries = block_on_stream(stream_from_futures_reader(reader, Some("entry")));
+    assert_eq!(titles(entries), vec!["</a:entry>", "two", "three & more"]);
+}
+
+#[test]
+fn stream_of_items_from_tokio_reader() {
+    let entries = block_on_stream(stream_from_tokio_reader(FEED.as_bytes(), Some("entry")));
+    assert_eq!(titles(entries), vec!["</a:entry>", "two", "three & more"]);
+
+    // The title isn't an entry, but the entries after it are still read
+    let mut entries = block_on_stream(stream_from_tokio_reader::<_, Entry>(FEED.as_bytes(), None));
+    assert!(entries.next().unwrap().is_err());
+    assert_eq!(titles(entries), vec!["</a:entry>", "two", "three & more"]);
+
+    let truncated = &FEED.as_bytes()[..FEED.len() - 20];
+    let entries = block_on_stream(stream_from_tokio_reader::<_, Entry>(truncated, Some("entry")));
+    let results = entries.collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn whole_document_from_async_readers() {
+    let s = r##"<Entry id="7"><title>Seven</title></Entry>"##;
+    let expected = Entry {
+        id: 7,
+        title: "Seven".to_string(),
+    };
+
+    let entry: Entry = block_on(from_tokio_reader(s.as_bytes())).unwrap();
+    assert_eq!(entry, expected);
+
+    let reader = Trickle {
+        bytes: s.as_bytes(),
+        ready: false,
+    };
+    let entry: Entry = block_on(from_futures_reader(reader)).unwrap();
+    assert_eq!(entry, expected);
+}
+
+#[test]
+fn async_readers_with_config() {
+    let feed = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\
+        <!DOCTYPE feed [<!ENTITY site \"Caf\xe9\">]>\
+        <feed><entry id=\"1\"><title>&site; &amp; &more;</title></entry><entry id=\"2\" title=\"two\"/></feed>";
+    let config = DeserializerConfig::new().entity("more", "more");
+
+    let entries = stream_from_tokio_reader(&feed[..], Some("entry")).with_config(config.clone());
+    assert_eq!(titles(block_on_stream(entries)), vec!["Caf\u{e9} & more", "two"]);
+
+    let mut entries = block_on_stream(
+        stream_from_tokio_reader::<_, Entry>(&feed[..], Some("entry"))
+            .with_config(config.clone().max_elements(3)),
+    );
+    assert!(entries.next().unwrap().is_ok());
+    let error = entries.next().unwrap().unwrap_err();
+    assert!(matches!(*error.kind(), ErrorKind::ElementLimitExceeded(3)), "{:?}", error.kind());
+    assert!(entries.next().is_none());
+
+    let mut entries = block_on_stream(
+        stream_from_tokio_reader::<_, Entry>(&feed[..], Some("entry"))
+            .with_config(config.clone().max_document_size(100)),
+    );
+    let error = entries.next().unwrap().unwrap_err();
+    assert!(matches!(*error.kind(), ErrorKind::DocumentSizeLimitExceeded(100)), "{:?}", error.kind());
+
+    let s = "<Entry id=\"7\"><title>&more;</title></Entry>";
+    let entry: Entry = block_on(from_tokio_reader(s.as_bytes()).with_config(config.clone())).unwrap();
+    assert_eq!(entry.title, "more");
+    let error = block_on(from_tokio_reader::<_, Entry>(s.as_bytes()).with_config(config.max_document_size(10)))
+        .unwrap_err();
+    assert!(matches!(*error.kind(), ErrorKind::DocumentSizeLimitExceeded(10)), "{:?}", error.kind());
+}
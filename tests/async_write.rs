@@ -0,0 +1,144 @@
+#![cfg(feature = "tokio")]
+
+#[macro_use]
+extern crate serde_derive;
+extern crate futures;
+extern crate serde;
+extern crate serde_xml_rs;
+extern crate tokio;
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::executor::{block_on, block_on_stream};
+use futures::SinkExt;
+use serde_xml_rs::{stream_from_tokio_reader, stream_to_tokio_writer, to_tokio_writer};
+use tokio::io::AsyncWrite;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Entry {
+    id: u32,
+    title: String,
+}
+
+/// Accepts a few bytes at a time, returning `Pending` in between.
+#[derive(Default)]
+struct Trickle {
+    bytes: Vec<u8>,
+    ready: bool,
+    shut_down: bool,
+}
+
+impl AsyncWrite for Trickle {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        let len = buf.len().min(3);
+        self.bytes.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        self.shut_down = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn entries() -> Vec<Entry> {
+    (1..4)
+        .map(|id| Entry {
+            id,
+            title: format!("Entry & {}", id),
+        })
+        .collect()
+}
+
+#[test]
+fn whole_document_to_tokio_writer() {
+    let mut writer = Trickle::default();
+    block_on(to_tokio_writer(&mut writer, &entries()[0])).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.bytes).unwrap(),
+        "<Entry><id>1</id><title>Entry &amp; 1</title></Entry>"
+    );
+}
+
+#[test]
+fn stream_of_items_to_tokio_writer() {
+    let mut writer = Trickle::default();
+    {
+        let mut sink = stream_to_tokio_writer(&mut writer, "feed", Some("entry"));
+        for entry in entries() {
+            block_on(sink.send(entry)).unwrap();
+        }
+        block_on(sink.close()).unwrap();
+    }
+    assert!(writer.shut_down);
+    assert_eq!(
+        String::from_utf8(writer.bytes.clone()).unwrap(),
+        "<feed><entry><id>1</id><title>Entry &amp; 1</title></entry>\
+         <entry><id>2</id><title>Entry &amp; 2</title></entry>\
+         <entry><id>3</id><title>Entry &amp; 3</title></entry></feed>"
+    );
+
+    let read = block_on_stream(stream_from_tokio_reader(&writer.bytes[..], Some("entry")))
+        .collect::<Result<Vec<Entry>, _>>()
+        .unwrap();
+    assert_eq!(read, entries());
+
+    let mut buffer = Vec::new();
+    {
+        let mut sink = stream_to_tokio_writer(&mut buffer, "feed", None);
+        block_on(sink.send(Entry { id: 7, title: "Seven".to_string() })).unwrap();
+        block_on(sink.close()).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "<feed><Entry><id>7</id><title>Seven</title></Entry></feed>"
+    );
+}
+
+#[derive(Serialize)]
+struct Checked {
+    id: u32,
+    #[serde(serialize_with = "non_empty")]
+    title: String,
+}
+
+fn non_empty<S: serde::Serializer>(title: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if title.is_empty() {
+        return Err(serde::ser::Error::custom("empty title"));
+    }
+    serializer.serialize_str(title)
+}
+
+#[test]
+fn failed_item_to_tokio_writer() {
+    let mut buffer = Vec::new();
+    {
+        let mut sink = stream_to_tokio_writer(&mut buffer, "feed", Some("entry"));
+        let checked = |id: u32, title: &str| Checked { id, title: title.to_string() };
+        block_on(sink.send(checked(1, "One"))).unwrap();
+        assert!(block_on(sink.send(checked(2, ""))).is_err());
+        block_on(sink.send(checked(3, "Three"))).unwrap();
+        block_on(sink.close()).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "<feed><entry><id>1</id><title>One</title></entry>\
+         <entry><id>3</id><title>Three</title></entry></feed>"
+    );
+}
@@ -1,8 +1,14 @@
+extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_xml_rs;
 
-use serde_xml_rs::{from_str, to_string};
+use std::collections::BTreeMap;
+
+use serde::de::value::{Error as ValueError, SeqDeserializer};
+use serde::Deserialize;
+use serde_xml_rs::{from_element, from_slice, from_str, to_element, to_string, CData, Element, ErrorKind, InnerXml, List};
+use serde_xml_rs::element::OwnedName;
 
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -77,3 +83,713 @@ fn round_trip_list_of_enums() {
     let deserialized_nodes: Nodes = from_str(serialized_nodes.as_str()).unwrap();
     assert_eq!(deserialized_nodes, nodes);
 }
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Header {
+    #[serde(rename = "@id")]
+    id: String,
+    version: String,
+    draft: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Message {
+    #[serde(flatten)]
+    header: Header,
+    body: String,
+    #[serde(flatten)]
+    extra: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Envelope {
+    message: Message,
+}
+
+#[test]
+fn flattened_struct() {
+    let src = r#"
+        <Envelope>
+            <message id="42">
+                <version>2</version>
+                <draft>true</draft>
+                <body>Hello</body>
+                <priority>high</priority>
+                <retries>3</retries>
+            </message>
+        </Envelope>"#;
+    let mut extra = BTreeMap::new();
+    extra.insert("priority".to_string(), "high".to_string());
+    extra.insert("retries".to_string(), "3".to_string());
+    let should_be = Envelope {
+        message: Message {
+            header: Header {
+                id: "42".to_string(),
+                version: "2".to_string(),
+                draft: "true".to_string(),
+            },
+            body: "Hello".to_string(),
+            extra,
+        },
+    };
+
+    let envelope: Envelope = from_str(src).unwrap();
+    assert_eq!(envelope, should_be);
+
+    let reserialized = to_string(&envelope).unwrap();
+    assert_eq!(
+        reserialized,
+        "<Envelope><message id=\"42\"><version>2</version><draft>true</draft><body>Hello</body>\
+         <priority>high</priority><retries>3</retries></message></Envelope>"
+    );
+
+    let envelope: Envelope = from_str(&reserialized).unwrap();
+    assert_eq!(envelope, should_be);
+    assert_eq!(from_element::<Envelope>(to_element(&envelope).unwrap()).unwrap(), should_be);
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct MessageHeader {
+    id: String,
+    #[serde(rename = "@version")]
+    version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Msg {
+    #[serde(flatten)]
+    header: MessageHeader,
+    body: String,
+}
+
+#[test]
+fn flattened_struct_at_the_root() {
+    let msg = Msg {
+        header: MessageHeader {
+            id: "x".to_string(),
+            version: "2".to_string(),
+        },
+        body: "b".to_string(),
+    };
+    // A struct with flattened fields is serialized as a map, which names
+    // the root element after the type
+    let markup = r#"<Msg version="2"><id>x</id><body>b</body></Msg>"#;
+    assert_eq!(to_string(&msg).unwrap(), markup);
+    assert_eq!(to_element(&msg).unwrap().to_string(), markup);
+    assert_eq!(from_str::<Msg>(markup).unwrap(), msg);
+    assert_eq!(from_element::<Msg>(to_element(&msg).unwrap()).unwrap(), msg);
+
+    // Attributes written as map entries need an element to go to
+    let mut entries = BTreeMap::new();
+    entries.insert("@version", "2");
+    assert!(to_string(&vec![entries]).is_err());
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+enum Inline {
+    #[serde(rename = "$value")]
+    Text(String),
+    #[serde(rename = "b")]
+    Bold(String),
+    #[serde(rename = "a")]
+    Link {
+        href: String,
+        #[serde(rename = "$value")]
+        text: String,
+    },
+    #[serde(rename = "br")]
+    Break,
+    #[serde(rename = "i")]
+    Italic(Vec<Inline>),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Paragraph {
+    #[serde(rename = "$value")]
+    content: Vec<Inline>,
+}
+
+#[test]
+fn mixed_content() {
+    let src = r#"<p>Hello <b>world</b>, again<br/>see <a href="/docs">the docs</a> &amp; more</p>"#;
+    let should_be = Paragraph {
+        content: vec![
+            Inline::Text("Hello ".to_string()),
+            Inline::Bold("world".to_string()),
+            Inline::Text(", again".to_string()),
+            Inline::Break,
+            Inline::Text("see ".to_string()),
+            Inline::Link {
+                href: "/docs".to_string(),
+                text: "the docs".to_string(),
+            },
+            Inline::Text(" & more".to_string()),
+        ],
+    };
+
+    let paragraph: Paragraph = from_str(src).unwrap();
+    assert_eq!(paragraph, should_be);
+
+    let reserialized = to_string(&paragraph).unwrap();
+    assert_eq!(
+        reserialized,
+        "<Paragraph>Hello <b>world</b>, again<br></br>see \
+         <a><href>/docs</href>the docs</a> &amp; more</Paragraph>"
+    );
+
+    let paragraph: Paragraph = from_str(&reserialized).unwrap();
+    assert_eq!(paragraph, should_be);
+}
+
+#[test]
+fn mixed_content_whitespace_and_nesting() {
+    let src = "<p>hello <b>world</b> <b>x</b> <i>very <b>much</b> so</i>\n</p>";
+    let should_be = Paragraph {
+        content: vec![
+            Inline::Text("hello ".to_string()),
+            Inline::Bold("world".to_string()),
+            Inline::Text(" ".to_string()),
+            Inline::Bold("x".to_string()),
+            Inline::Text(" ".to_string()),
+            Inline::Italic(vec![
+                Inline::Text("very ".to_string()),
+                Inline::Bold("much".to_string()),
+                Inline::Text(" so".to_string()),
+            ]),
+        ],
+    };
+
+    let paragraph: Paragraph = from_str(src).unwrap();
+    assert_eq!(paragraph, should_be);
+
+    let reserialized = to_string(&paragraph).unwrap();
+    assert_eq!(
+        reserialized,
+        "<Paragraph>hello <b>world</b> <b>x</b> <i>very <b>much</b> so</i></Paragraph>"
+    );
+    assert_eq!(from_str::<Paragraph>(&reserialized).unwrap(), should_be);
+    assert_eq!(from_element::<Paragraph>(to_element(&should_be).unwrap()).unwrap(), should_be);
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Quote {
+    id: u32,
+    payload: Element,
+    extra: Vec<Element>,
+}
+
+#[test]
+fn generic_elements() {
+    let src = r#"
+        <Envelope xmlns:q="urn:quote">
+            <id>7</id>
+            <payload kind="quote">
+                <!-- unchanged -->
+                <q:price currency="EUR">1.5</q:price>
+                <note><![CDATA[a <b> c]]></note>
+            </payload>
+            <extra>one</extra>
+            <extra xmlns="urn:other"><deep/></extra>
+        </Envelope>
+    "#;
+
+    let envelope: Quote = from_str(src).unwrap();
+    assert_eq!(envelope.id, 7);
+    let payload = &envelope.payload;
+    assert_eq!(payload.attribute("kind"), Some("quote"));
+    let price = payload.get_child("price").unwrap();
+    assert_eq!(
+        price.name,
+        OwnedName {
+            local_name: "price".to_string(),
+            namespace: Some("urn:quote".to_string()),
+            prefix: Some("q".to_string()),
+        }
+    );
+    assert_eq!(price.text(), "1.5");
+    assert_eq!(payload.get_child("note").unwrap().text(), "a <b> c");
+    assert_eq!(envelope.extra[0].text(), "one");
+    assert_eq!(envelope.extra[1].elements().count(), 1);
+
+    // The element keeps its whitespace
+    let reserialized = to_string(&envelope).unwrap();
+    assert_eq!(
+        reserialized,
+        "<Quote><id>7</id>\
+         <payload xmlns:q=\"urn:quote\" kind=\"quote\">\n                <!-- unchanged -->\
+         \n                <q:price currency=\"EUR\">1.5</q:price>\
+         \n                <note><![CDATA[a <b> c]]></note>\n            </payload>\
+         <extra xmlns:q=\"urn:quote\">one</extra>\
+         <extra xmlns=\"urn:other\" xmlns:q=\"urn:quote\"><deep></deep></extra></Quote>"
+    );
+
+    let envelope2: Quote = from_str(&reserialized).unwrap();
+    assert_eq!(envelope2, envelope);
+}
+
+
+#[test]
+fn element_conversions() {
+    let src = r#"<Paragraph>Hello <b>world</b><br/>see <a href="/docs">the docs</a></Paragraph>"#;
+    let paragraph: Paragraph = from_str(src).unwrap();
+
+    let element = to_element(&paragraph).unwrap();
+    assert_eq!(element.to_string(), to_string(&paragraph).unwrap());
+    assert_eq!(from_element::<Paragraph>(element).unwrap(), paragraph);
+
+    let element: Element = from_str(src).unwrap();
+    assert_eq!(from_element::<Paragraph>(element).unwrap(), paragraph);
+
+    let src = r#"<Quote><id>7</id><payload><a>1</a></payload><extra>x</extra><extra>y</extra></Quote>"#;
+    let quote: Quote = from_element(from_str(src).unwrap()).unwrap();
+    assert_eq!(quote.extra.len(), 2);
+    let element = to_element(&quote).unwrap();
+    assert_eq!(element.to_string(), src);
+    assert_eq!(from_element::<Quote>(element).unwrap(), quote);
+
+    assert!(to_element(&"text").is_err());
+}
+
+#[test]
+fn comments_that_cannot_be_written() {
+    let mut element = Element::new("doc");
+    element.children.push(serde_xml_rs::Node::Comment(" fine - really ".to_string()));
+    assert_eq!(to_string(&element).unwrap(), "<doc><!-- fine - really --></doc>");
+
+    for text in &["a -- b", "trailing -"] {
+        let mut child = Element::new("child");
+        child.children.push(serde_xml_rs::Node::Comment(text.to_string()));
+        let mut element = Element::new("doc");
+        element.children.push(serde_xml_rs::Node::Element(child));
+        for error in [to_string(&element).unwrap_err(), to_element(&element).unwrap_err()] {
+            match *error.kind() {
+                ErrorKind::InvalidComment(ref comment) => assert_eq!(comment, text),
+                ref other => panic!("unexpected error {:?}", other),
+            }
+        }
+    }
+}
+
+
+#[test]
+fn elements_as_read() {
+    let src = "<doc>\n  <?render fast?>\n  <p> Hello <b>world</b> </p>\n</doc>";
+    let element: Element = from_str(src).unwrap();
+    assert_eq!(
+        element.children[1],
+        serde_xml_rs::Node::ProcessingInstruction {
+            name: "render".to_string(),
+            data: Some("fast".to_string()),
+        }
+    );
+    assert_eq!(element.get_child("p").unwrap().text(), " Hello  ");
+    assert_eq!(to_string(&element).unwrap(), src);
+    assert_eq!(to_element(&element).unwrap(), element);
+    assert_eq!(from_element::<Element>(element.clone()).unwrap(), element);
+
+    // A sequence of strings is no element
+    let tokens = SeqDeserializer::<_, ValueError>::new(vec!["<", "doc", "", "", "0", "0", ">"].into_iter());
+    assert!(Element::deserialize(tokens).is_err());
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Listing {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@note")]
+    note: Option<String>,
+    title: String,
+}
+
+#[test]
+fn optional_attributes() {
+    let listings = vec![
+        (
+            Listing {
+                id: 1,
+                note: Some("\"new\" & <hot>".to_string()),
+                title: "Lamp".to_string(),
+            },
+            r#"<Listing id="1" note="&quot;new&quot; &amp; &lt;hot&gt;"><title>Lamp</title></Listing>"#,
+        ),
+        (
+            Listing {
+                id: 2,
+                note: None,
+                title: "Desk".to_string(),
+            },
+            r#"<Listing id="2"><title>Desk</title></Listing>"#,
+        ),
+    ];
+
+    for (listing, markup) in listings {
+        let serialized = to_string(&listing).unwrap();
+        assert_eq!(serialized, markup);
+        assert_eq!(from_str::<Listing>(&serialized).unwrap(), listing);
+
+        let element = to_element(&listing).unwrap();
+        assert_eq!(element.attribute("id"), Some(&*listing.id.to_string()));
+        assert_eq!(element.attribute("note"), listing.note.as_deref());
+        assert_eq!(from_element::<Listing>(element).unwrap(), listing);
+    }
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Badge {
+    label: String,
+    #[serde(rename = "$other")]
+    other: Element,
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@color")]
+    color: Option<String>,
+}
+
+#[test]
+fn attributes_after_content() {
+    let badge: Badge = from_str(r#"<Badge id="7" mode="a"><label>New</label><extra/></Badge>"#).unwrap();
+    assert_eq!(badge.color, None);
+
+    let written = r#"<Badge mode="a" id="7"><label>New</label><extra></extra></Badge>"#;
+    assert_eq!(to_string(&badge).unwrap(), written);
+    assert_eq!(to_element(&badge).unwrap().to_string(), written);
+    assert_eq!(from_str::<Badge>(written).unwrap(), badge);
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Path {
+    #[serde(rename = "@refs")]
+    refs: List<Vec<String>>,
+    #[serde(rename = "@size")]
+    size: (u32, u32),
+    points: List<Vec<f64>>,
+    origin: List<(i32, i32)>,
+}
+
+#[test]
+fn whitespace_separated_lists() {
+    let path = Path {
+        refs: List(vec!["a".to_string(), "b".to_string()]),
+        size: (640, 480),
+        points: List(vec![1.5, -2.0, 3.25]),
+        origin: List((0, 7)),
+    };
+    let markup = r#"<Path refs="a b" size="640 480"><points>1.5 -2 3.25</points><origin>0 7</origin></Path>"#;
+
+    let serialized = to_string(&path).unwrap();
+    assert_eq!(serialized, markup);
+    assert_eq!(to_element(&path).unwrap().to_string(), markup);
+
+    // `size` is only read as a list with the `xs_lists` option
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Wrapped {
+        refs: List<Vec<String>>,
+        points: List<Vec<f64>>,
+        origin: List<(i32, i32)>,
+    }
+    let wrapped: Wrapped = from_str(&serialized).unwrap();
+    assert_eq!(wrapped.refs, path.refs);
+    assert_eq!(wrapped.points, path.points);
+    assert_eq!(wrapped.origin, path.origin);
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Reading {
+    #[serde(rename = "@low")]
+    low: f32,
+    #[serde(rename = "@high")]
+    high: f64,
+    value: f64,
+}
+
+#[test]
+fn special_floats() {
+    let reading = Reading {
+        low: f32::NEG_INFINITY,
+        high: f64::INFINITY,
+        value: 2.5e-3,
+    };
+    let markup = r#"<Reading low="-INF" high="INF"><value>0.0025</value></Reading>"#;
+    assert_eq!(to_string(&reading).unwrap(), markup);
+    assert_eq!(from_str::<Reading>(markup).unwrap(), reading);
+
+    let reading: Reading = from_str(r#"<Reading low="-1E4" high="+.5"><value>NaN</value></Reading>"#)
+        .unwrap();
+    assert_eq!((reading.low, reading.high), (-1e4, 0.5));
+    assert!(reading.value.is_nan());
+    assert_eq!(to_string(&reading).unwrap(), r#"<Reading low="-10000" high="0.5"><value>NaN</value></Reading>"#);
+
+    assert!(from_str::<Reading>(r#"<Reading low="inf" high="1"><value>1</value></Reading>"#).is_err());
+    assert!(from_str::<Reading>(r#"<Reading low="1" high="1"><value>infinity</value></Reading>"#).is_err());
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Ledger {
+    #[serde(rename = "@id")]
+    id: u128,
+    balance: i128,
+    history: List<Vec<i128>>,
+}
+
+#[test]
+fn wide_integers() {
+    let ledger = Ledger {
+        id: u128::MAX,
+        balance: i128::MIN,
+        history: List(vec![-1, 170141183460469231731687303715884105727]),
+    };
+    let markup = format!(
+        r#"<Ledger id="{}"><balance>{}</balance><history>-1 {}</history></Ledger>"#,
+        u128::MAX,
+        i128::MIN,
+        i128::MAX
+    );
+    assert_eq!(to_string(&ledger).unwrap(), markup);
+    assert_eq!(from_str::<Ledger>(&markup).unwrap(), ledger);
+    assert_eq!(from_element::<Ledger>(to_element(&ledger).unwrap()).unwrap(), ledger);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Ids {
+        #[serde(rename = "@ids")]
+        ids: List<Vec<u128>>,
+        pair: List<(u128, i128)>,
+    }
+    let markup = format!(r#"<Ids ids="1 {max}"><pair>{max} {min}</pair></Ids>"#, max = u128::MAX, min = i128::MIN);
+    let ids: Ids = from_str(&markup).unwrap();
+    assert_eq!(ids.ids, List(vec![1, u128::MAX]));
+    assert_eq!(ids.pair, List((u128::MAX, i128::MIN)));
+
+    // A list of one number reads as a sequence, whatever the width
+    let narrow = from_str::<List<u64>>("<n>1</n>").unwrap_err().to_string();
+    let wide = from_str::<List<u128>>("<n>1</n>").unwrap_err().to_string();
+    assert_eq!(wide, narrow.replace("u64", "u128"));
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Snippet {
+    #[serde(rename = "@lang")]
+    lang: CData<String>,
+    code: CData<String>,
+    lines: CData<u32>,
+}
+
+#[test]
+fn cdata_sections() {
+    let snippet = Snippet {
+        lang: CData("js".to_string()),
+        code: CData("if (a[b[0]]>1 && c < 2) {}".to_string()),
+        lines: CData(1),
+    };
+    let markup = concat!(
+        r#"<Snippet lang="js">"#,
+        "<code><![CDATA[if (a[b[0]]]]><![CDATA[>1 && c < 2) {}]]></code>",
+        "<lines><![CDATA[1]]></lines>",
+        "</Snippet>"
+    );
+    assert_eq!(to_string(&snippet).unwrap(), markup);
+    assert_eq!(from_str::<Snippet>(markup).unwrap(), snippet);
+    let element = to_element(&snippet).unwrap();
+    assert_eq!(element.to_string(), markup);
+    assert_eq!(from_element::<Snippet>(element).unwrap(), snippet);
+
+    let escaped = r#"<Snippet lang="js"><code>if (a[b[0]]&gt;1 &amp;&amp; c &lt; 2) {}</code><lines>1</lines></Snippet>"#;
+    assert_eq!(from_str::<Snippet>(escaped).unwrap(), snippet);
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Post {
+    #[serde(rename = "@id")]
+    id: u32,
+    title: String,
+    body: InnerXml,
+    footer: InnerXml,
+}
+
+#[test]
+fn inner_xml() {
+    let markup = concat!(
+        r#"<Post id="7"><title>Notes</title>"#,
+        r#"<body>Some <em class="x">fish &amp; chips</em><!-- yum --><br/> <![CDATA[a < b]]></body>"#,
+        "<footer></footer>",
+        "</Post>"
+    );
+    let post = Post {
+        id: 7,
+        title: "Notes".to_string(),
+        body: InnerXml(
+            r#"Some <em class="x">fish &amp; chips</em><!-- yum --><br></br> <![CDATA[a < b]]>"#.to_string(),
+        ),
+        footer: InnerXml(String::new()),
+    };
+    assert_eq!(from_str::<Post>(markup).unwrap(), post);
+
+    let written = to_string(&post).unwrap();
+    assert_eq!(from_str::<Post>(&written).unwrap(), post);
+    assert_eq!(from_element::<Post>(to_element(&post).unwrap()).unwrap(), post);
+}
+
+#[test]
+fn inner_xml_from_slice() {
+    // Read from a slice, the content is taken as it is written
+    let body = r#"Some <em class='x'>fish &amp; chips</em><?keep this?><br/> &#65;"#;
+    let markup = format!("<Post id=\"7\"><title>Notes</title><body>{}</body><footer/></Post>", body);
+    let post = from_slice::<Post>(markup.as_bytes()).unwrap();
+    assert_eq!(post.body, InnerXml(body.to_string()));
+    assert_eq!(post.footer, InnerXml(String::new()));
+}
+
+#[test]
+fn inner_xml_namespaces() {
+    let markup = concat!(
+        r#"<Post xmlns:x="urn:x" id="7"><title>Notes</title>"#,
+        r#"<body><x:p x:lang="en">Hi</x:p><p xmlns:x="urn:y"><x:b/></p></body>"#,
+        "<footer/>",
+        "</Post>"
+    );
+    let expected = concat!(
+        r#"<x:p xmlns:x="urn:x" x:lang="en">Hi</x:p>"#,
+        r#"<p xmlns:x="urn:y"><x:b></x:b></p>"#
+    );
+    let from_reader = from_str::<Post>(markup).unwrap();
+    let from_slice = from_slice::<Post>(markup.as_bytes()).unwrap();
+    assert_eq!(from_reader.body, InnerXml(expected.to_string()));
+    assert_eq!(from_slice.body, from_reader.body);
+
+    let element = to_element(&from_reader).unwrap();
+    let body = element.get_child("body").unwrap();
+    assert_eq!(body.get_child("p").unwrap().name.namespace.as_deref(), Some("urn:x"));
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Settings {
+    #[serde(rename = "@version")]
+    version: u32,
+    #[serde(rename = "$other")]
+    other: Element,
+    name: String,
+}
+
+#[test]
+fn other_attributes_and_children() {
+    let markup = concat!(
+        r#"<Settings version="2" xmlns:ext="urn:ext" ext:flag="on" mode="fast">"#,
+        r#"<ext:plugin id="p"><path>a &amp; b</path></ext:plugin>"#,
+        "<name>main</name>",
+        "<future>yes</future>",
+        "</Settings>"
+    );
+    let settings: Settings = from_str(markup).unwrap();
+    assert_eq!(settings.version, 2);
+    assert_eq!(settings.name, "main");
+    assert_eq!(settings.other.attribute("mode"), Some("fast"));
+    assert_eq!(settings.other.attribute("flag"), Some("on"));
+    assert_eq!(settings.other.get_child("future").unwrap().text(), "yes");
+    assert_eq!(settings.other.get_child("plugin").unwrap().attribute("id"), Some("p"));
+
+    // Unknown children are written where the field is, and namespace
+    // declarations ahead of the attributes
+    let written = concat!(
+        r#"<Settings xmlns:ext="urn:ext" version="2" ext:flag="on" mode="fast">"#,
+        r#"<ext:plugin id="p"><path>a &amp; b</path></ext:plugin>"#,
+        "<future>yes</future>",
+        "<name>main</name>",
+        "</Settings>"
+    );
+    assert_eq!(to_string(&settings).unwrap(), written);
+    assert_eq!(to_element(&settings).unwrap().to_string(), written);
+    assert_eq!(from_str::<Settings>(written).unwrap(), settings);
+    assert_eq!(from_element::<Settings>(to_element(&settings).unwrap()).unwrap(), settings);
+
+    let scoped = r#"<Settings version="1" xmlns:ext="urn:ext"><ext:a/><name>x</name></Settings>"#;
+    assert_eq!(
+        to_string(&from_str::<Settings>(scoped).unwrap()).unwrap(),
+        r#"<Settings version="1"><ext:a xmlns:ext="urn:ext"></ext:a><name>x</name></Settings>"#
+    );
+
+    let known = r#"<Settings version="3"><name>x</name></Settings>"#;
+    let settings: Settings = from_str(known).unwrap();
+    assert!(settings.other.attributes.is_empty() && settings.other.children.is_empty());
+    assert_eq!(to_string(&settings).unwrap(), known);
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct LooseSettings {
+    #[serde(rename = "$other")]
+    other: BTreeMap<String, String>,
+    name: String,
+}
+
+#[test]
+fn other_has_to_be_an_element() {
+    // A map would drop the names of attributes and the markup of children
+    let markup = r#"<LooseSettings mode="fast"><name>x</name><future>yes</future></LooseSettings>"#;
+    assert!(from_str::<LooseSettings>(markup).is_err());
+
+    let mut other = BTreeMap::new();
+    other.insert("future".to_string(), "yes".to_string());
+    let settings = LooseSettings { other, name: "x".to_string() };
+    assert!(to_string(&settings).is_err());
+    assert!(to_element(&settings).is_err());
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Widget {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(rename = "$attrs")]
+    attrs: BTreeMap<String, String>,
+    label: String,
+}
+
+#[test]
+fn attribute_maps() {
+    let markup = concat!(
+        r#"<Widget xmlns:data="urn:data" id="w1" data:color="red" size="10">"#,
+        "<label>OK</label>",
+        "</Widget>"
+    );
+    let widget: Widget = from_str(markup).unwrap();
+    let expected: BTreeMap<String, String> = vec![
+        ("data:color", "red"),
+        ("id", "w1"),
+        ("size", "10"),
+        ("xmlns:data", "urn:data"),
+    ].into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    assert_eq!(widget.id, "w1");
+    assert_eq!(widget.attrs, expected);
+
+    // `id` is written once, by the field that comes first
+    let written = concat!(
+        r#"<Widget xmlns:data="urn:data" id="w1" data:color="red" size="10">"#,
+        "<label>OK</label>",
+        "</Widget>"
+    );
+    assert_eq!(to_string(&widget).unwrap(), written);
+    assert_eq!(to_element(&widget).unwrap().to_string(), written);
+    assert_eq!(from_str::<Widget>(written).unwrap(), widget);
+    assert_eq!(from_element::<Widget>(to_element(&widget).unwrap()).unwrap(), widget);
+}
+
+#[test]
+fn attribute_maps_with_invalid_names() {
+    for name in &[r#"a="1"><evil/><x y"#, "", "1a", "a:", ":a", "a:b:c", "xmlns:"] {
+        let mut attrs = BTreeMap::new();
+        attrs.insert(name.to_string(), "1".to_string());
+        let widget = Widget { id: "w1".to_string(), attrs, label: "OK".to_string() };
+        assert!(to_string(&widget).is_err(), "{:?} was written", name);
+        assert!(to_element(&widget).is_err(), "{:?} was built", name);
+    }
+}
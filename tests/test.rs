@@ -1,11 +1,15 @@
 #[macro_use]
 extern crate serde_derive;
+extern crate serde;
 extern crate serde_xml_rs;
 
 extern crate log;
 extern crate simple_logger;
 
-use serde_xml_rs::from_str;
+use std::borrow::Cow;
+
+use serde_xml_rs::{from_reader_at, from_slice, from_str, Deserializer, DeserializerConfig,
+                   ErrorKind};
 
 #[derive(Debug, Deserialize, PartialEq)]
 struct Item {
@@ -138,7 +142,7 @@ fn collection_of_enums() {
     let s = r##"
         <enums>
             <A>test</A>
-            <B name="hello" flag="t" />
+            <B name="hello" flag="true" />
             <C />
         </enums>
     "##;
@@ -159,3 +163,668 @@ fn collection_of_enums() {
         }
     );
 }
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Untagged enums buffer their content, which keeps text as strings
+#[derive(Debug, Deserialize, PartialEq)]
+struct Vertex {
+    x: String,
+    y: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Shape {
+    Circle { radius: String },
+    Polygon { point: Vec<Vertex> },
+    Label(String),
+    Empty,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Drawing {
+    #[serde(rename = "shape")]
+    shapes: Vec<Shape>,
+}
+
+#[test]
+fn untagged_enums() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let s = r##"
+        <drawing>
+            <shape radius="1.5" />
+            <shape>
+                <point x="0" y="0" />
+                <point x="-1" y="1" />
+                <point x="1" y="1" />
+            </shape>
+            <shape>hello</shape>
+            <shape />
+        </drawing>
+    "##;
+
+    let drawing: Drawing = from_str(s).unwrap();
+
+    assert_eq!(
+        drawing,
+        Drawing {
+            shapes: vec![
+                Shape::Circle { radius: "1.5".to_string() },
+                Shape::Polygon {
+                    point: vec![
+                        Vertex { x: "0".to_string(), y: "0".to_string() },
+                        Vertex { x: "-1".to_string(), y: "1".to_string() },
+                        Vertex { x: "1".to_string(), y: "1".to_string() },
+                    ],
+                },
+                Shape::Label("hello".to_string()),
+                Shape::Empty,
+            ],
+        }
+    );
+
+    let nested = format!("<drawing><shape>{}{}</shape></drawing>", "<a>".repeat(20), "</a>".repeat(20));
+    let mut de = DeserializerConfig::new().max_depth(10).create_deserializer(nested.as_bytes());
+    let error = Drawing::deserialize(&mut de).unwrap_err();
+    assert!(matches!(*error.kind(), ErrorKind::DepthLimitExceeded(10)), "{:?}", error.kind());
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Reference {
+    Attribute {
+        #[serde(rename = "@id")]
+        id: String,
+    },
+    Element { id: String },
+}
+
+#[test]
+fn untagged_enums_with_prefixed_attributes() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let parse = |s: &str| {
+        let mut de = DeserializerConfig::new()
+            .prefix_attributes(true)
+            .create_deserializer(s.as_bytes());
+        Reference::deserialize(&mut de).unwrap()
+    };
+
+    assert_eq!(
+        parse(r#"<ref id="a"/>"#),
+        Reference::Attribute { id: "a".to_string() }
+    );
+    assert_eq!(
+        parse(r#"<ref><id>b</id></ref>"#),
+        Reference::Element { id: "b".to_string() }
+    );
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct BorrowedItem<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    source: Cow<'a, str>,
+    #[serde(borrow)]
+    escaped: Cow<'a, str>,
+    #[serde(borrow)]
+    cdata: Cow<'a, str>,
+}
+
+#[test]
+fn borrowed_strings_from_slice() {
+    let _ = simple_logger::init();
+
+    let s = r##"
+        <item name="hello">
+            <source>  world.rs  </source>
+            <escaped>a &amp; b</escaped>
+            <cdata><![CDATA[c]]></cdata>
+        </item>
+    "##;
+
+    let item: BorrowedItem = from_slice(s.as_bytes()).unwrap();
+
+    assert_eq!(item.name, "hello");
+    let borrowed = |value: &Cow<str>| match *value {
+        Cow::Borrowed(_) => true,
+        Cow::Owned(_) => false,
+    };
+    assert_eq!(item.source, "world.rs");
+    assert!(borrowed(&item.source));
+    assert_eq!(item.escaped, "a & b");
+    assert!(!borrowed(&item.escaped));
+    assert_eq!(item.cdata, "c");
+    assert!(!borrowed(&item.cdata));
+}
+
+#[test]
+fn borrowed_str_requires_verbatim_text() {
+    let _ = simple_logger::init();
+
+    let s = r##"<item name="a &amp; b"><source>x</source></item>"##;
+    let item: Result<BorrowedItem, _> = from_slice(s.as_bytes());
+    assert!(item.is_err());
+}
+
+#[test]
+fn borrowed_strings_from_other_encodings() {
+    let _ = simple_logger::init();
+
+    let s = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\
+        <item name=\"caf\xe9\"><source>na\xefve</source><escaped/><cdata/></item>";
+    let item: Result<BorrowedItem, _> = from_slice(s);
+    assert!(item.is_err());
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct CowItem<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        #[serde(borrow)]
+        source: Cow<'a, str>,
+    }
+    let item: CowItem = from_slice(s).unwrap();
+    assert_eq!(item.name, "caf\u{e9}");
+    assert_eq!(item.source, "na\u{ef}ve");
+    assert!(matches!(item.source, Cow::Owned(_)));
+}
+
+#[test]
+fn stream_of_child_items() {
+    let _ = simple_logger::init();
+
+    let s = r##"
+        <feed>
+            <title>News</title>
+            <entry name="a" source="a.rs" />
+            <entry><name>b</name><source>b.rs</source></entry>
+            <link>ignored</link>
+            <entry name="c" source="c.rs" />
+        </feed>
+    "##;
+
+    let items = Deserializer::new_from_reader(s.as_bytes())
+        .into_stream_named::<Item>("entry")
+        .map(|item| item.unwrap().name)
+        .collect::<Vec<_>>();
+    assert_eq!(items, vec!["a", "b", "c"]);
+
+    // Items that fail are skipped, whether they fail inside or at their start
+    let s = r##"<list><n>1</n><n>x</n><n><m>2</m></n><n>3</n></list>"##;
+    let mut numbers = Deserializer::new_from_reader(s.as_bytes()).into_stream::<u32>();
+    assert_eq!(numbers.next().unwrap().unwrap(), 1);
+    assert!(numbers.next().unwrap().is_err());
+    assert!(numbers.next().unwrap().is_err());
+    assert_eq!(numbers.next().unwrap().unwrap(), 3);
+    assert!(numbers.next().is_none());
+
+    let s = r##"<feed><entry><name>a</name><source>a.rs</source></entry><entry><name>b</name></entry><other/><entry name="c" source="c.rs"/></feed>"##;
+    let items = Deserializer::new_from_reader(s.as_bytes())
+        .into_stream::<Item>()
+        .map(|item| item.map(|item| item.name).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(items, vec![Some("a".to_string()), None, None, Some("c".to_string())]);
+
+    // Malformed markup ends the stream
+    let s = r##"<list><n>1</n><n>2</m><n>3</n></list>"##;
+    let mut numbers = Deserializer::new_from_reader(s.as_bytes()).into_stream::<u32>();
+    assert_eq!(numbers.next().unwrap().unwrap(), 1);
+    assert!(numbers.next().unwrap().is_err());
+    assert!(numbers.next().is_none());
+}
+
+#[test]
+fn subtree_selected_by_path() {
+    let _ = simple_logger::init();
+
+    let s = r##"
+        <Envelope>
+            <Header><item name="header" source="h.rs" /></Header>
+            <Body>
+                <item name="first" source="a.rs" />
+                <Nested><item name="nested" source="n.rs" /></Nested>
+                <item><name>second</name><source>b.rs</source></item>
+            </Body>
+        </Envelope>
+    "##;
+
+    let item: Item = from_reader_at(s.as_bytes(), "/Envelope/Body/*").unwrap();
+    assert_eq!(item.name, "first");
+
+    let names = Deserializer::new_from_reader(s.as_bytes())
+        .into_stream_at::<Item>("/Envelope/*/item")
+        .unwrap()
+        .map(|item| item.unwrap().name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["header", "first", "second"]);
+
+    let missing: Result<Item, _> = from_reader_at(s.as_bytes(), "/Envelope/Footer");
+    match *missing.unwrap_err().kind() {
+        ErrorKind::PathNotFound(ref path) => assert_eq!(path, "/Envelope/Footer"),
+        ref other => panic!("unexpected error {:?}", other),
+    }
+    assert!(from_reader_at::<_, Item>(s.as_bytes(), "Envelope//Body").is_err());
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Flags {
+    enabled: bool,
+    visible: bool,
+    #[serde(rename = "$value")]
+    archived: bool,
+}
+
+#[test]
+fn booleans_in_attributes_and_elements() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let flags: Flags = from_str(r#"<flags enabled="false" visible="1">true</flags>"#).unwrap();
+    assert_eq!(
+        flags,
+        Flags {
+            enabled: false,
+            visible: true,
+            archived: true,
+        }
+    );
+
+    let s = r#"<flags enabled="yes" visible="no">maybe</flags>"#;
+    let error = from_str::<Flags>(s).unwrap_err().to_string();
+    assert!(error.contains("\"yes\""), "{}", error);
+    assert!(error.contains("true, false, 1, 0"), "{}", error);
+
+    let s = r#"<flags enabled="Yes" visible="off">ON</flags>"#;
+    let mut de = DeserializerConfig::new()
+        .bool_yes_no(true)
+        .bool_on_off(true)
+        .bool_case_insensitive(true)
+        .create_deserializer(s.as_bytes());
+    let flags = Flags::deserialize(&mut de).unwrap();
+    assert_eq!(
+        flags,
+        Flags {
+            enabled: true,
+            visible: false,
+            archived: true,
+        }
+    );
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Offer {
+    #[serde(rename = "@sku")]
+    sku: String,
+    #[serde(rename = "@discount")]
+    discount: Option<u32>,
+    #[serde(rename = "@code")]
+    code: Option<String>,
+    price: u32,
+}
+
+#[test]
+fn optional_attributes() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let offer: Offer = from_str(r#"<offer sku="A1" code="SAVE"><price>5</price></offer>"#).unwrap();
+    assert_eq!(
+        offer,
+        Offer {
+            sku: "A1".to_string(),
+            discount: None,
+            code: Some("SAVE".to_string()),
+            price: 5,
+        }
+    );
+
+    let s = r#"<offer sku="A1" discount="" code=""><price>5</price></offer>"#;
+    assert!(from_str::<Offer>(s).is_err());
+
+    let mut de = DeserializerConfig::new()
+        .empty_attributes_as_none(true)
+        .create_deserializer(s.as_bytes());
+    let offer = Offer::deserialize(&mut de).unwrap();
+    assert_eq!(
+        offer,
+        Offer {
+            sku: "A1".to_string(),
+            discount: None,
+            code: None,
+            price: 5,
+        }
+    );
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Polygon {
+    refs: Vec<String>,
+    coords: Vec<f64>,
+    origin: (i32, i32),
+    empty: Vec<u32>,
+    point: Vec<Point>,
+}
+
+#[test]
+fn whitespace_separated_lists() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let s = r#"
+        <shape refs="a  b
+            c">
+            <coords>1.5 2.0 3.25</coords>
+            <origin> -1 7 </origin>
+            <empty/>
+            <point><x>1</x><y>2</y></point>
+            <point><x>3</x><y>4</y></point>
+        </shape>
+    "#;
+    let mut de = DeserializerConfig::new()
+        .xs_lists(true)
+        .create_deserializer(s.as_bytes());
+    let shape = Polygon::deserialize(&mut de).unwrap();
+    assert_eq!(
+        shape,
+        Polygon {
+            refs: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            coords: vec![1.5, 2.0, 3.25],
+            origin: (-1, 7),
+            empty: vec![],
+            point: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        }
+    );
+
+    let s = r#"<shape refs=""><coords/><origin>1 2 3</origin><empty/></shape>"#;
+    let mut de = DeserializerConfig::new()
+        .xs_lists(true)
+        .create_deserializer(s.as_bytes());
+    let error = Polygon::deserialize(&mut de).unwrap_err().to_string();
+    assert!(error.contains("too many items"), "{}", error);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Playlist {
+    name: String,
+    track: Vec<Track>,
+    note: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Track {
+    title: String,
+}
+
+#[test]
+fn interleaved_sequences() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let s = r#"
+        <playlist>
+            <track><title>One</title></track>
+            <note>first</note>
+            <track><title>Two</title></track>
+            <name>Mix</name>
+            <!-- the last one -->
+            <track><title>Three</title></track>
+            <note>last</note>
+        </playlist>
+    "#;
+    assert!(from_str::<Playlist>(s).is_err());
+
+    let mut de = DeserializerConfig::new()
+        .interleaved_sequences(true)
+        .create_deserializer(s.as_bytes());
+    let playlist = Playlist::deserialize(&mut de).unwrap();
+    assert_eq!(
+        playlist,
+        Playlist {
+            name: "Mix".to_string(),
+            track: vec![
+                Track { title: "One".to_string() },
+                Track { title: "Two".to_string() },
+                Track { title: "Three".to_string() },
+            ],
+            note: vec!["first".to_string(), "last".to_string()],
+        }
+    );
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Stock {
+    #[serde(rename = "@count")]
+    count: u32,
+    #[serde(rename = "@offset")]
+    offset: i16,
+    mask: u8,
+    price: f64,
+}
+
+#[test]
+fn lenient_numbers() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    let s = r#"<stock count=" 1,000 " offset="-0x10"><mask>+0XfF</mask><price>+12,345.5</price></stock>"#;
+    let error = from_str::<Stock>(s).unwrap_err();
+    match *error.kind() {
+        ErrorKind::InvalidNumber(ref number_type, ref text) => {
+            assert_eq!(number_type, "u32");
+            assert_eq!(text, " 1,000 ");
+        },
+        ref other => panic!("unexpected error {:?}", other),
+    }
+
+    let mut de = DeserializerConfig::new()
+        .numbers_trim_whitespace(true)
+        .numbers_hex(true)
+        .numbers_thousands_separator(Some(','))
+        .create_deserializer(s.as_bytes());
+    let stock = Stock::deserialize(&mut de).unwrap();
+    assert_eq!(
+        stock,
+        Stock {
+            count: 1000,
+            offset: -16,
+            mask: 255,
+            price: 12345.5,
+        }
+    );
+
+    for count in ["1,,000", "1,2,3", "1234,567", "1,0000", "1,000,"] {
+        let s = format!(r#"<stock count="{}" offset="0"><mask>1</mask><price>1</price></stock>"#, count);
+        let mut de = DeserializerConfig::new()
+            .numbers_thousands_separator(Some(','))
+            .create_deserializer(s.as_bytes());
+        let error = Stock::deserialize(&mut de).unwrap_err().to_string();
+        assert_eq!(error, format!("invalid u32: '{}'", count));
+    }
+
+    let s = r#"<stock count="0" offset="-1,000"><mask>1</mask><price>1,000.250,5</price></stock>"#;
+    let mut de = DeserializerConfig::new()
+        .numbers_thousands_separator(Some(','))
+        .create_deserializer(s.as_bytes());
+    let error = Stock::deserialize(&mut de).unwrap_err().to_string();
+    assert_eq!(error, "invalid f64: '1,000.250,5'");
+}
+
+#[test]
+fn security_limits() {
+    use serde::Deserialize;
+    use serde_xml_rs::Element;
+
+    let _ = simple_logger::init();
+
+    fn read<T: for<'de> Deserialize<'de>>(config: DeserializerConfig, s: &str) -> Result<T, serde_xml_rs::Error> {
+        T::deserialize(&mut config.create_deserializer(s.as_bytes()))
+    }
+
+    let nested = format!("<a>{}{}</a>", "<a>".repeat(20), "</a>".repeat(20));
+    let s = r#"<item name="banana" source="store"/>"#;
+    assert!(read::<Item>(DeserializerConfig::new().max_depth(1), s).is_ok());
+    assert!(read::<Element>(DeserializerConfig::new().max_depth(21), &nested).is_ok());
+
+    fn check(config: DeserializerConfig, s: &str, expected: fn(&ErrorKind) -> bool) {
+        let error = read::<Item>(config.clone(), s).unwrap_err();
+        assert!(expected(error.kind()), "{:?}", error.kind());
+        let error = read::<Element>(config, s).unwrap_err();
+        assert!(expected(error.kind()), "{:?}", error.kind());
+    }
+
+    check(DeserializerConfig::new().max_depth(20), &nested, |kind| {
+        matches!(*kind, ErrorKind::DepthLimitExceeded(20))
+    });
+    check(DeserializerConfig::new().max_attributes(1), s, |kind| {
+        matches!(*kind, ErrorKind::AttributeLimitExceeded(1))
+    });
+    check(
+        DeserializerConfig::new().max_text_length(5),
+        "<item><name>banana</name><source>store</source></item>",
+        |kind| matches!(*kind, ErrorKind::TextLengthLimitExceeded(5)),
+    );
+    check(DeserializerConfig::new().max_document_size(20), s, |kind| {
+        matches!(*kind, ErrorKind::DocumentSizeLimitExceeded(20))
+    });
+    check(
+        DeserializerConfig::new().max_elements(2),
+        "<item><name>banana</name><source>store</source></item>",
+        |kind| matches!(*kind, ErrorKind::ElementLimitExceeded(2)),
+    );
+
+    // The limits hold while reading, so endless input fails rather than
+    // being buffered
+    fn endless(config: DeserializerConfig, start: &'static str, fill: u8) -> serde_xml_rs::Error {
+        use std::io::{self, Read};
+
+        let reader = start.as_bytes().chain(io::repeat(fill));
+        Item::deserialize(&mut config.create_deserializer(reader)).unwrap_err()
+    }
+
+    let config = DeserializerConfig::new().max_text_length(100);
+    for &(start, fill) in &[("<item><name>", b'x'), ("<item name=\"", b'x'), ("<item><!--", b'x')] {
+        let error = endless(config.clone(), start, fill);
+        assert!(matches!(*error.kind(), ErrorKind::TextLengthLimitExceeded(100)), "{:?}", error.kind());
+    }
+    let error = endless(config.max_attributes(10), "<item><name>", b'x');
+    assert!(matches!(*error.kind(), ErrorKind::Syntax(_)), "{:?}", error.kind());
+    let error = endless(DeserializerConfig::new().max_document_size(1000), "<item>", b' ');
+    assert!(matches!(*error.kind(), ErrorKind::DocumentSizeLimitExceeded(1000)), "{:?}", error.kind());
+}
+
+#[test]
+fn entities() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Notice {
+        #[serde(rename = "@owner")]
+        owner: String,
+        text: String,
+    }
+
+    let s = r#"<notice owner="&company;"><text>&copy;&nbsp;2026 &company; &amp; caf&eacute;s</text></notice>"#;
+    let config = DeserializerConfig::new()
+        .html_entities(true)
+        .entity("company", "ACME");
+    let notice = Notice::deserialize(&mut config.create_deserializer(s.as_bytes())).unwrap();
+    assert_eq!(
+        notice,
+        Notice {
+            owner: "ACME".to_string(),
+            text: "\u{a9}\u{a0}2026 ACME & caf\u{e9}s".to_string(),
+        }
+    );
+
+    // Custom entities take precedence over the HTML ones
+    let config = DeserializerConfig::new()
+        .html_entities(true)
+        .entities(vec![("company", "Initech"), ("copy", "(c)")]);
+    let notice = Notice::deserialize(&mut config.create_deserializer(s.as_bytes())).unwrap();
+    assert_eq!(notice.text, "(c)\u{a0}2026 Initech & caf\u{e9}s");
+
+    assert!(from_str::<Notice>(s).is_err());
+}
+
+#[test]
+fn internal_dtd() {
+    use serde::Deserialize;
+
+    let _ = simple_logger::init();
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Manual {
+        #[serde(rename = "@version")]
+        version: Option<String>,
+        #[serde(rename = "@lang")]
+        lang: Option<String>,
+        #[serde(rename = "@edition")]
+        edition: Option<u32>,
+        title: String,
+    }
+
+    let s = r#"<?xml version="1.0"?>
+        <!DOCTYPE manual [
+            <!ENTITY product "Widget &amp; Co.">
+            <!ATTLIST manual
+                version CDATA #FIXED "1.0"
+                lang NMTOKEN "en"
+                edition CDATA #IMPLIED>
+        ]>
+        <manual lang="de"><title>&product; manual</title></manual>"#;
+
+    let config = DeserializerConfig::new().internal_dtd(true);
+    let manual = Manual::deserialize(&mut config.clone().create_deserializer(s.as_bytes())).unwrap();
+    let expected = Manual {
+        version: Some("1.0".to_string()),
+        lang: Some("de".to_string()),
+        edition: None,
+        title: "Widget & Co. manual".to_string(),
+    };
+    assert_eq!(manual, expected);
+    let manual = Manual::deserialize(&mut config.create_slice_deserializer(s.as_bytes())).unwrap();
+    assert_eq!(manual, expected);
+
+    // Without the option, entities are still expanded but no defaults added
+    let manual: Manual = from_str(s).unwrap();
+    assert_eq!(manual.version, None);
+    assert_eq!(manual.title, "Widget & Co. manual");
+
+    let laughs = r#"<!DOCTYPE lolz [
+            <!ENTITY lol "lol">
+            <!ENTITY lol1 "&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;">
+            <!ENTITY lol2 "&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;">
+            <!ENTITY lol3 "&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;">
+            <!ATTLIST lolz title CDATA "&lol3;">
+        ]>
+        <lolz><title>&lol3;</title></lolz>"#;
+    for config in [
+        DeserializerConfig::new().max_entity_expansion_length(100),
+        DeserializerConfig::new().max_entity_expansion_depth(2),
+        DeserializerConfig::new().internal_dtd(true).max_entity_expansion_length(100),
+    ] {
+        let error = Manual::deserialize(&mut config.create_deserializer(laughs.as_bytes())).unwrap_err();
+        assert!(
+            matches!(*error.kind(), ErrorKind::EntityExpansionLimitExceeded),
+            "{:?}",
+            error.kind()
+        );
+    }
+}
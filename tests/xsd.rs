@@ -0,0 +1,114 @@
+#![cfg(all(feature = "chrono", feature = "time"))]
+
+extern crate chrono;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_xml_rs;
+extern crate time;
+
+use serde_xml_rs::{from_str, to_string};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Meeting {
+    #[serde(rename = "@start", with = "serde_xml_rs::xsd::chrono::date_time")]
+    start: chrono::DateTime<chrono::FixedOffset>,
+    #[serde(rename = "@length", with = "serde_xml_rs::xsd::chrono::duration")]
+    length: chrono::TimeDelta,
+    #[serde(with = "serde_xml_rs::xsd::chrono::naive_date_time")]
+    booked: chrono::NaiveDateTime,
+    #[serde(with = "serde_xml_rs::xsd::chrono::date")]
+    day: chrono::NaiveDate,
+    #[serde(with = "serde_xml_rs::xsd::chrono::time_of_day")]
+    doors: chrono::NaiveTime,
+}
+
+#[test]
+fn chrono_types() {
+    let s = r#"
+        <Meeting start="2026-10-18T12:00:00+02:00" length="P1DT2H">
+            <booked>2026-09-01T08:30:15.25</booked>
+            <day>2026-10-18Z</day>
+            <doors>11:45:00</doors>
+        </Meeting>
+    "#;
+    let meeting: Meeting = from_str(s).unwrap();
+    let offset = chrono::FixedOffset::east_opt(7200).unwrap();
+    let day = chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    assert_eq!(
+        meeting.start,
+        chrono::DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00").unwrap()
+    );
+    assert_eq!(*meeting.start.offset(), offset);
+    assert_eq!(
+        meeting.booked,
+        chrono::NaiveDate::from_ymd_opt(2026, 9, 1)
+            .unwrap()
+            .and_hms_milli_opt(8, 30, 15, 250)
+            .unwrap()
+    );
+    assert_eq!(meeting.day, day);
+    assert_eq!(meeting.doors, chrono::NaiveTime::from_hms_opt(11, 45, 0).unwrap());
+    assert_eq!(meeting.length, chrono::TimeDelta::hours(26));
+
+    let serialized = to_string(&meeting).unwrap();
+    assert_eq!(
+        serialized,
+        "<Meeting start=\"2026-10-18T12:00:00+02:00\" length=\"P1DT2H\">\
+         <booked>2026-09-01T08:30:15.25</booked><day>2026-10-18</day>\
+         <doors>11:45:00</doors></Meeting>"
+    );
+    assert_eq!(from_str::<Meeting>(&serialized).unwrap(), meeting);
+
+    let utc: Meeting = from_str(&s.replace("+02:00", "")).unwrap();
+    assert_eq!(utc.start.offset().local_minus_utc(), 0);
+
+    let error = from_str::<Meeting>(&s.replace("P1DT2H", "P1M")).unwrap_err().to_string();
+    assert!(error.contains("xs:duration"), "{}", error);
+    assert!(from_str::<Meeting>(&s.replace("2026-10-18Z", "2026-02-30")).is_err());
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Shift {
+    #[serde(rename = "@start", with = "serde_xml_rs::xsd::time::offset_date_time")]
+    start: time::OffsetDateTime,
+    #[serde(rename = "@break", with = "serde_xml_rs::xsd::time::duration")]
+    pause: time::Duration,
+    #[serde(with = "serde_xml_rs::xsd::time::primitive_date_time")]
+    booked: time::PrimitiveDateTime,
+    #[serde(with = "serde_xml_rs::xsd::time::date")]
+    day: time::Date,
+    #[serde(with = "serde_xml_rs::xsd::time::time_of_day")]
+    doors: time::Time,
+}
+
+#[test]
+fn time_types() {
+    let s = r#"
+        <Shift start="2026-10-18T22:00:00-05:30" break="-PT1M30.5S">
+            <booked>2026-09-01T08:30:15</booked>
+            <day>2026-10-18</day>
+            <doors>21:45:00.125+01:00</doors>
+        </Shift>
+    "#;
+    let shift: Shift = from_str(s).unwrap();
+    let day = time::Date::from_calendar_date(2026, time::Month::October, 18).unwrap();
+    let offset = time::UtcOffset::from_hms(-5, -30, 0).unwrap();
+    assert_eq!(
+        shift.start,
+        time::PrimitiveDateTime::new(day, time::Time::from_hms(22, 0, 0).unwrap()).assume_offset(offset)
+    );
+    assert_eq!(shift.start.offset(), offset);
+    assert_eq!(shift.booked.time(), time::Time::from_hms(8, 30, 15).unwrap());
+    assert_eq!(shift.day, day);
+    assert_eq!(shift.doors, time::Time::from_hms_milli(21, 45, 0, 125).unwrap());
+    assert_eq!(shift.pause, -time::Duration::milliseconds(90_500));
+
+    let serialized = to_string(&shift).unwrap();
+    assert_eq!(
+        serialized,
+        "<Shift start=\"2026-10-18T22:00:00-05:30\" break=\"-PT1M30.5S\">\
+         <booked>2026-09-01T08:30:15</booked><day>2026-10-18</day>\
+         <doors>21:45:00.125</doors></Shift>"
+    );
+    assert_eq!(from_str::<Shift>(&serialized).unwrap(), shift);
+}